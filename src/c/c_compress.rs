@@ -1,4 +1,13 @@
-﻿use crate::h_compress::ENetSymbol;
+﻿#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(non_camel_case_types)]
+
+use crate::c_host::enet_host_compress;
+use crate::enet_buffer_as_slice;
+use crate::h_compress::ENetSymbol;
+use crate::h_enet::{ENetCompressor, ENetError, ENetHost};
+use crate::h_win32::ENetBuffer;
+use std::any::Any;
 
 pub const ENET_RANGE_CODER_TOP: u32 = 1 << 24;
 pub const ENET_RANGE_CODER_BOTTOM: u32 = 1 << 16;
@@ -21,4 +30,755 @@ pub fn enet_range_coder_create() -> ENetRangeCoder {
     }
 }
 
-pub fn enet_range_coder_destroy(_: ENetRangeCoder) {}
+pub fn enet_range_coder_destroy(_: Option<Box<dyn Any>>) {}
+
+fn enet_symbol_create(
+    rangeCoder: &mut ENetRangeCoder,
+    nextSymbol: &mut usize,
+    value: u8,
+    count: u8,
+) -> usize {
+    let symbol = *nextSymbol;
+    *nextSymbol += 1;
+
+    rangeCoder.symbols[symbol] = ENetSymbol {
+        value,
+        count,
+        under: count as u16,
+        left: 0,
+        right: 0,
+        symbols: 0,
+        escapes: 0,
+        total: 0,
+        parent: 0,
+    };
+
+    symbol
+}
+
+fn enet_context_create(
+    rangeCoder: &mut ENetRangeCoder,
+    nextSymbol: &mut usize,
+    escapes: u32,
+    minimum: u32,
+) -> usize {
+    let context = enet_symbol_create(rangeCoder, nextSymbol, 0, 0);
+
+    let symbol = &mut rangeCoder.symbols[context];
+    symbol.escapes = escapes as u16;
+    symbol.total = (escapes + 256 * minimum) as u16;
+    symbol.symbols = 0;
+
+    context
+}
+
+fn enet_symbol_rescale(symbols: &mut [ENetSymbol], mut symbol: usize) -> u16 {
+    let mut total: u16 = 0;
+
+    loop {
+        let current = &mut symbols[symbol];
+        current.count -= current.count >> 1;
+        current.under = current.count as u16;
+
+        if current.left != 0 {
+            let left = symbol + current.left as usize;
+            let under = enet_symbol_rescale(symbols, left);
+            symbols[symbol].under = symbols[symbol].under.wrapping_add(under);
+        }
+
+        total = total.wrapping_add(symbols[symbol].under);
+
+        if symbols[symbol].right == 0 {
+            break;
+        }
+
+        symbol += symbols[symbol].right as usize;
+    }
+
+    total
+}
+
+fn enet_context_rescale(rangeCoder: &mut ENetRangeCoder, context: usize, minimum: u32) {
+    let symbols = &mut rangeCoder.symbols;
+
+    let first = symbols[context].symbols as usize;
+    symbols[context].total = if first != 0 {
+        enet_symbol_rescale(symbols, context + first)
+    } else {
+        0
+    };
+
+    let context = &mut symbols[context];
+    context.escapes -= context.escapes >> 1;
+    context.total = context
+        .total
+        .wrapping_add((context.escapes as u32 + 256 * minimum) as u16);
+}
+
+fn enet_symbol_set_parent(
+    rangeCoder: &mut ENetRangeCoder,
+    predicted: &mut u16,
+    parent: Option<usize>,
+    symbol: usize,
+) {
+    match parent {
+        Some(parent) => rangeCoder.symbols[parent].parent = symbol as u16,
+        None => *predicted = symbol as u16,
+    }
+}
+
+fn enet_context_encode(
+    rangeCoder: &mut ENetRangeCoder,
+    nextSymbol: &mut usize,
+    context: usize,
+    value: u8,
+    update: u32,
+    minimum: u32,
+) -> (usize, u16, u16) {
+    let mut under = (value as u32 * minimum) as u16;
+    let mut count = minimum as u16;
+
+    if rangeCoder.symbols[context].symbols == 0 {
+        let symbol = enet_symbol_create(rangeCoder, nextSymbol, value, update as u8);
+        rangeCoder.symbols[context].symbols = (symbol - context) as u16;
+
+        return (symbol, under, count);
+    }
+
+    let mut node = context + rangeCoder.symbols[context].symbols as usize;
+    loop {
+        let current = &mut rangeCoder.symbols[node];
+
+        if value < current.value {
+            current.under = current.under.wrapping_add(update as u16);
+            if current.left != 0 {
+                node += current.left as usize;
+                continue;
+            }
+
+            let symbol = enet_symbol_create(rangeCoder, nextSymbol, value, update as u8);
+            rangeCoder.symbols[node].left = (symbol - node) as u16;
+
+            return (symbol, under, count);
+        } else if value > current.value {
+            under = under.wrapping_add(current.under);
+            if current.right != 0 {
+                node += current.right as usize;
+                continue;
+            }
+
+            let symbol = enet_symbol_create(rangeCoder, nextSymbol, value, update as u8);
+            rangeCoder.symbols[node].right = (symbol - node) as u16;
+
+            return (symbol, under, count);
+        } else {
+            count = count.wrapping_add(current.count as u16);
+            under = under.wrapping_add(current.under.wrapping_sub(current.count as u16));
+            current.under = current.under.wrapping_add(update as u16);
+            current.count = current.count.wrapping_add(update as u8);
+
+            return (node, under, count);
+        }
+    }
+}
+
+fn enet_range_coder_encode(
+    encodeLow: &mut u32,
+    encodeRange: &mut u32,
+    under: u32,
+    count: u32,
+    total: u32,
+    outData: &mut [u8],
+    outPosition: &mut usize,
+) -> bool {
+    *encodeRange /= total;
+    *encodeLow = encodeLow.wrapping_add(under.wrapping_mul(*encodeRange));
+    *encodeRange = encodeRange.wrapping_mul(count);
+
+    loop {
+        if (*encodeLow ^ encodeLow.wrapping_add(*encodeRange)) >= ENET_RANGE_CODER_TOP {
+            if *encodeRange >= ENET_RANGE_CODER_BOTTOM {
+                break;
+            }
+
+            *encodeRange = encodeLow.wrapping_neg() & (ENET_RANGE_CODER_BOTTOM - 1);
+        }
+
+        if *outPosition >= outData.len() {
+            return false;
+        }
+
+        outData[*outPosition] = (*encodeLow >> 24) as u8;
+        *outPosition += 1;
+
+        *encodeRange <<= 8;
+        *encodeLow <<= 8;
+    }
+
+    true
+}
+
+pub fn enet_range_coder_compress(
+    context: Option<&mut Box<dyn Any>>,
+    data: &[&[u8]],
+    inBuffers: &[ENetBuffer],
+    inBufferCount: usize,
+    inLimit: usize,
+    outData: &mut [u8],
+    outLimit: usize,
+) -> usize {
+    let rangeCoder = match context.and_then(|context| context.downcast_mut::<ENetRangeCoder>()) {
+        Some(x) => x,
+        None => return 0,
+    };
+
+    if inBufferCount == 0 || inLimit == 0 {
+        return 0;
+    }
+
+    let outData = &mut outData[..outLimit];
+    let mut outPosition = 0;
+    let mut encodeLow: u32 = 0;
+    let mut encodeRange: u32 = !0;
+    let mut predicted: u16 = 0;
+    let mut order = 0;
+    let mut nextSymbol = 0;
+
+    let mut inBuffer = 0;
+    let mut inData = enet_buffer_as_slice!(inBuffers[inBuffer], data);
+    let mut inPosition = 0;
+    inBuffer += 1;
+
+    let mut root = enet_context_create(
+        rangeCoder,
+        &mut nextSymbol,
+        ENET_CONTEXT_ESCAPE_MINIMUM,
+        ENET_CONTEXT_SYMBOL_MINIMUM,
+    );
+
+    loop {
+        while inPosition >= inData.len() {
+            if inBuffer >= inBufferCount {
+                break;
+            }
+
+            inData = enet_buffer_as_slice!(inBuffers[inBuffer], data);
+            inPosition = 0;
+            inBuffer += 1;
+        }
+
+        if inPosition >= inData.len() {
+            break;
+        }
+
+        let value = inData[inPosition];
+        inPosition += 1;
+
+        let mut parent: Option<usize> = None;
+        let mut subcontext = predicted as usize;
+        let mut encoded = false;
+
+        while subcontext != root {
+            let (symbol, under, count) = enet_context_encode(
+                rangeCoder,
+                &mut nextSymbol,
+                subcontext,
+                value,
+                ENET_SUBCONTEXT_SYMBOL_DELTA,
+                0,
+            );
+            enet_symbol_set_parent(rangeCoder, &mut predicted, parent, symbol);
+            parent = Some(symbol);
+
+            let context = &mut rangeCoder.symbols[subcontext];
+            let total = context.total as u32;
+
+            if count > 0 {
+                if !enet_range_coder_encode(
+                    &mut encodeLow,
+                    &mut encodeRange,
+                    context.escapes as u32 + under as u32,
+                    count as u32,
+                    total,
+                    outData,
+                    &mut outPosition,
+                ) {
+                    return 0;
+                }
+            } else {
+                if context.escapes > 0
+                    && (context.escapes as u32) < total
+                    && !enet_range_coder_encode(
+                        &mut encodeLow,
+                        &mut encodeRange,
+                        0,
+                        context.escapes as u32,
+                        total,
+                        outData,
+                        &mut outPosition,
+                    )
+                {
+                    return 0;
+                }
+
+                context.escapes = context
+                    .escapes
+                    .wrapping_add(ENET_SUBCONTEXT_ESCAPE_DELTA as u16);
+                context.total = context
+                    .total
+                    .wrapping_add(ENET_SUBCONTEXT_ESCAPE_DELTA as u16);
+            }
+
+            context.total = context
+                .total
+                .wrapping_add(ENET_SUBCONTEXT_SYMBOL_DELTA as u16);
+            if count as u32 > 0xFF - 2 * ENET_SUBCONTEXT_SYMBOL_DELTA
+                || context.total as u32 > ENET_RANGE_CODER_BOTTOM - 0x100
+            {
+                enet_context_rescale(rangeCoder, subcontext, 0);
+            }
+
+            if count > 0 {
+                encoded = true;
+                break;
+            }
+
+            subcontext = rangeCoder.symbols[subcontext].parent as usize;
+        }
+
+        if !encoded {
+            let (symbol, under, count) = enet_context_encode(
+                rangeCoder,
+                &mut nextSymbol,
+                root,
+                value,
+                ENET_CONTEXT_SYMBOL_DELTA,
+                ENET_CONTEXT_SYMBOL_MINIMUM,
+            );
+            enet_symbol_set_parent(rangeCoder, &mut predicted, parent, symbol);
+
+            let context = &mut rangeCoder.symbols[root];
+            let total = context.total as u32;
+
+            if !enet_range_coder_encode(
+                &mut encodeLow,
+                &mut encodeRange,
+                context.escapes as u32 + under as u32,
+                count as u32,
+                total,
+                outData,
+                &mut outPosition,
+            ) {
+                return 0;
+            }
+
+            context.total = context.total.wrapping_add(ENET_CONTEXT_SYMBOL_DELTA as u16);
+            if count as u32 > 0xFF - 2 * ENET_CONTEXT_SYMBOL_DELTA + ENET_CONTEXT_SYMBOL_MINIMUM
+                || context.total as u32 > ENET_RANGE_CODER_BOTTOM - 0x100
+            {
+                enet_context_rescale(rangeCoder, root, ENET_CONTEXT_SYMBOL_MINIMUM);
+            }
+        }
+
+        if order >= ENET_SUBCONTEXT_ORDER {
+            predicted = rangeCoder.symbols[predicted as usize].parent;
+        } else {
+            order += 1;
+        }
+
+        if nextSymbol >= rangeCoder.symbols.len() - ENET_SUBCONTEXT_ORDER as usize {
+            nextSymbol = 0;
+            root = enet_context_create(
+                rangeCoder,
+                &mut nextSymbol,
+                ENET_CONTEXT_ESCAPE_MINIMUM,
+                ENET_CONTEXT_SYMBOL_MINIMUM,
+            );
+            predicted = 0;
+            order = 0;
+        }
+    }
+
+    while encodeLow != 0 {
+        if outPosition >= outData.len() {
+            return 0;
+        }
+
+        outData[outPosition] = (encodeLow >> 24) as u8;
+        outPosition += 1;
+
+        encodeLow <<= 8;
+    }
+
+    outPosition
+}
+
+fn enet_range_coder_read(
+    decodeCode: u32,
+    decodeLow: u32,
+    decodeRange: &mut u32,
+    total: u32,
+) -> u16 {
+    *decodeRange /= total;
+
+    (decodeCode.wrapping_sub(decodeLow) / *decodeRange) as u16
+}
+
+fn enet_range_coder_decode(
+    decodeLow: &mut u32,
+    decodeRange: &mut u32,
+    decodeCode: &mut u32,
+    under: u32,
+    count: u32,
+    inData: &[u8],
+    inPosition: &mut usize,
+) {
+    *decodeLow = decodeLow.wrapping_add(under.wrapping_mul(*decodeRange));
+    *decodeRange = decodeRange.wrapping_mul(count);
+
+    loop {
+        if (*decodeLow ^ decodeLow.wrapping_add(*decodeRange)) >= ENET_RANGE_CODER_TOP {
+            if *decodeRange >= ENET_RANGE_CODER_BOTTOM {
+                break;
+            }
+
+            *decodeRange = decodeLow.wrapping_neg() & (ENET_RANGE_CODER_BOTTOM - 1);
+        }
+
+        *decodeCode <<= 8;
+        if *inPosition < inData.len() {
+            *decodeCode |= inData[*inPosition] as u32;
+            *inPosition += 1;
+        }
+
+        *decodeRange <<= 8;
+        *decodeLow <<= 8;
+    }
+}
+
+fn enet_context_decode(
+    rangeCoder: &mut ENetRangeCoder,
+    nextSymbol: &mut usize,
+    context: usize,
+    code: u16,
+    update: u32,
+    minimum: u32,
+    createSymbols: bool,
+) -> Option<(usize, u8, u16, u16)> {
+    let mut under: u16 = 0;
+    let count = minimum as u16;
+
+    if rangeCoder.symbols[context].symbols == 0 {
+        if !createSymbols {
+            return None;
+        }
+
+        let value = (code as u32 / minimum) as u8;
+        under = (code as u32 - code as u32 % minimum) as u16;
+        let symbol = enet_symbol_create(rangeCoder, nextSymbol, value, update as u8);
+        rangeCoder.symbols[context].symbols = (symbol - context) as u16;
+
+        return Some((symbol, value, under, count));
+    }
+
+    let mut node = context + rangeCoder.symbols[context].symbols as usize;
+    loop {
+        let current = &mut rangeCoder.symbols[node];
+        let after = (under as u32)
+            .wrapping_add(current.under as u32)
+            .wrapping_add((current.value as u32 + 1) * minimum) as u16;
+        let before = (current.count as u32 + minimum) as u16;
+
+        if code >= after {
+            under = under.wrapping_add(current.under);
+            if current.right != 0 {
+                node += current.right as usize;
+                continue;
+            }
+
+            if !createSymbols {
+                return None;
+            }
+
+            let value = (current.value as u32 + 1 + (code - after) as u32 / minimum) as u8;
+            under = (code as u32 - (code - after) as u32 % minimum) as u16;
+            let symbol = enet_symbol_create(rangeCoder, nextSymbol, value, update as u8);
+            rangeCoder.symbols[node].right = (symbol - node) as u16;
+
+            return Some((symbol, value, under, count));
+        } else if (code as i32) < after as i32 - before as i32 {
+            current.under = current.under.wrapping_add(update as u16);
+            if current.left != 0 {
+                node += current.left as usize;
+                continue;
+            }
+
+            if !createSymbols {
+                return None;
+            }
+
+            let distance = (after as i32 - before as i32 - code as i32 - 1) as u32;
+            let value = (current.value as i32 - 1 - (distance / minimum) as i32) as u8;
+            under = (code as u32).wrapping_sub(distance % minimum) as u16;
+            let symbol = enet_symbol_create(rangeCoder, nextSymbol, value, update as u8);
+            rangeCoder.symbols[node].left = (symbol - node) as u16;
+
+            return Some((symbol, value, under, count));
+        } else {
+            let value = current.value;
+            let count = count.wrapping_add(current.count as u16);
+            under = after.wrapping_sub(before);
+            current.under = current.under.wrapping_add(update as u16);
+            current.count = current.count.wrapping_add(update as u8);
+
+            return Some((node, value, under, count));
+        }
+    }
+}
+
+pub fn enet_range_coder_decompress(
+    context: Option<&mut Box<dyn Any>>,
+    inData: &[u8],
+    inLimit: usize,
+    outData: &mut [u8],
+    outLimit: usize,
+) -> usize {
+    let rangeCoder = match context.and_then(|context| context.downcast_mut::<ENetRangeCoder>()) {
+        Some(x) => x,
+        None => return 0,
+    };
+
+    if inLimit == 0 {
+        return 0;
+    }
+
+    let inData = &inData[..inLimit];
+    let outData = &mut outData[..outLimit];
+    let mut inPosition = 0;
+    let mut outPosition = 0;
+    let mut decodeLow: u32 = 0;
+    let mut decodeCode: u32 = 0;
+    let mut decodeRange: u32 = !0;
+    let mut predicted: u16 = 0;
+    let mut order = 0;
+    let mut nextSymbol = 0;
+
+    let mut root = enet_context_create(
+        rangeCoder,
+        &mut nextSymbol,
+        ENET_CONTEXT_ESCAPE_MINIMUM,
+        ENET_CONTEXT_SYMBOL_MINIMUM,
+    );
+
+    for shift in [24, 16, 8, 0] {
+        if inPosition < inData.len() {
+            decodeCode |= (inData[inPosition] as u32) << shift;
+            inPosition += 1;
+        }
+    }
+
+    loop {
+        let mut parent: Option<usize> = None;
+        let mut subcontext = predicted as usize;
+        let mut decoded: Option<(usize, u8)> = None;
+
+        while subcontext != root {
+            let context = &rangeCoder.symbols[subcontext];
+            let escapes = context.escapes as u32;
+            let total = context.total as u32;
+
+            if escapes == 0 || escapes >= total {
+                subcontext = context.parent as usize;
+                continue;
+            }
+
+            let mut code = enet_range_coder_read(decodeCode, decodeLow, &mut decodeRange, total);
+            if (code as u32) < escapes {
+                enet_range_coder_decode(
+                    &mut decodeLow,
+                    &mut decodeRange,
+                    &mut decodeCode,
+                    0,
+                    escapes,
+                    inData,
+                    &mut inPosition,
+                );
+
+                subcontext = rangeCoder.symbols[subcontext].parent as usize;
+                continue;
+            }
+            code = code.wrapping_sub(escapes as u16);
+
+            let (symbol, value, under, count) = match enet_context_decode(
+                rangeCoder,
+                &mut nextSymbol,
+                subcontext,
+                code,
+                ENET_SUBCONTEXT_SYMBOL_DELTA,
+                0,
+                false,
+            ) {
+                Some(x) => x,
+                None => return 0,
+            };
+
+            enet_range_coder_decode(
+                &mut decodeLow,
+                &mut decodeRange,
+                &mut decodeCode,
+                escapes + under as u32,
+                count as u32,
+                inData,
+                &mut inPosition,
+            );
+
+            let context = &mut rangeCoder.symbols[subcontext];
+            context.total = context
+                .total
+                .wrapping_add(ENET_SUBCONTEXT_SYMBOL_DELTA as u16);
+            if count as u32 > 0xFF - 2 * ENET_SUBCONTEXT_SYMBOL_DELTA
+                || context.total as u32 > ENET_RANGE_CODER_BOTTOM - 0x100
+            {
+                enet_context_rescale(rangeCoder, subcontext, 0);
+            }
+
+            decoded = Some((symbol, value));
+            break;
+        }
+
+        let (bottom, value) = match decoded {
+            Some(x) => x,
+            None => {
+                let context = &rangeCoder.symbols[root];
+                let escapes = context.escapes as u32;
+                let total = context.total as u32;
+
+                let mut code =
+                    enet_range_coder_read(decodeCode, decodeLow, &mut decodeRange, total);
+                if (code as u32) < escapes {
+                    enet_range_coder_decode(
+                        &mut decodeLow,
+                        &mut decodeRange,
+                        &mut decodeCode,
+                        0,
+                        escapes,
+                        inData,
+                        &mut inPosition,
+                    );
+
+                    break;
+                }
+                code = code.wrapping_sub(escapes as u16);
+
+                let (symbol, value, under, count) = match enet_context_decode(
+                    rangeCoder,
+                    &mut nextSymbol,
+                    root,
+                    code,
+                    ENET_CONTEXT_SYMBOL_DELTA,
+                    ENET_CONTEXT_SYMBOL_MINIMUM,
+                    true,
+                ) {
+                    Some(x) => x,
+                    None => return 0,
+                };
+
+                enet_range_coder_decode(
+                    &mut decodeLow,
+                    &mut decodeRange,
+                    &mut decodeCode,
+                    escapes + under as u32,
+                    count as u32,
+                    inData,
+                    &mut inPosition,
+                );
+
+                let context = &mut rangeCoder.symbols[root];
+                context.total = context.total.wrapping_add(ENET_CONTEXT_SYMBOL_DELTA as u16);
+                if count as u32 > 0xFF - 2 * ENET_CONTEXT_SYMBOL_DELTA + ENET_CONTEXT_SYMBOL_MINIMUM
+                    || context.total as u32 > ENET_RANGE_CODER_BOTTOM - 0x100
+                {
+                    enet_context_rescale(rangeCoder, root, ENET_CONTEXT_SYMBOL_MINIMUM);
+                }
+
+                (symbol, value)
+            }
+        };
+
+        let mut patch = predicted as usize;
+        while patch != subcontext {
+            let (symbol, _, count) = enet_context_encode(
+                rangeCoder,
+                &mut nextSymbol,
+                patch,
+                value,
+                ENET_SUBCONTEXT_SYMBOL_DELTA,
+                0,
+            );
+            enet_symbol_set_parent(rangeCoder, &mut predicted, parent, symbol);
+            parent = Some(symbol);
+
+            let context = &mut rangeCoder.symbols[patch];
+            if count == 0 {
+                context.escapes = context
+                    .escapes
+                    .wrapping_add(ENET_SUBCONTEXT_ESCAPE_DELTA as u16);
+                context.total = context
+                    .total
+                    .wrapping_add(ENET_SUBCONTEXT_ESCAPE_DELTA as u16);
+            }
+
+            context.total = context
+                .total
+                .wrapping_add(ENET_SUBCONTEXT_SYMBOL_DELTA as u16);
+            if count as u32 > 0xFF - 2 * ENET_SUBCONTEXT_SYMBOL_DELTA
+                || context.total as u32 > ENET_RANGE_CODER_BOTTOM - 0x100
+            {
+                enet_context_rescale(rangeCoder, patch, 0);
+            }
+
+            patch = rangeCoder.symbols[patch].parent as usize;
+        }
+        enet_symbol_set_parent(rangeCoder, &mut predicted, parent, bottom);
+
+        if outPosition >= outData.len() {
+            return 0;
+        }
+
+        outData[outPosition] = value;
+        outPosition += 1;
+
+        if order >= ENET_SUBCONTEXT_ORDER {
+            predicted = rangeCoder.symbols[predicted as usize].parent;
+        } else {
+            order += 1;
+        }
+
+        if nextSymbol >= rangeCoder.symbols.len() - ENET_SUBCONTEXT_ORDER as usize {
+            nextSymbol = 0;
+            root = enet_context_create(
+                rangeCoder,
+                &mut nextSymbol,
+                ENET_CONTEXT_ESCAPE_MINIMUM,
+                ENET_CONTEXT_SYMBOL_MINIMUM,
+            );
+            predicted = 0;
+            order = 0;
+        }
+    }
+
+    outPosition
+}
+
+pub fn enet_host_compress_with_range_coder(host: &mut ENetHost) -> Result<(), ENetError> {
+    let compressor = ENetCompressor {
+        context: Some(Box::new(enet_range_coder_create())),
+        compress: Some(enet_range_coder_compress),
+        decompress: Some(enet_range_coder_decompress),
+        destroy: Some(enet_range_coder_destroy),
+    };
+
+    enet_host_compress(host, Some(compressor));
+
+    Ok(())
+}
@@ -2,13 +2,18 @@
 #![allow(non_upper_case_globals)]
 #![allow(non_camel_case_types)]
 
+use crate::c_peer::*;
+use crate::c_protocol::*;
 use crate::c_win32::*;
+use crate::enet_host_get_mut_peer;
+use crate::h_enet::ENetSocketWait::*;
 use crate::h_enet::*;
 use crate::h_protocol::*;
+use crate::h_time::*;
 use crate::h_win32::*;
 use std::collections::VecDeque;
 
-pub fn enet_host_ping(host: &ENetHost, address: &ENetAddress) -> bool {
+pub fn enet_host_ping(host: &ENetHost, address: &ENetAddress) -> Result<(), ENetError> {
     let data: [u8; 1] = [0u8; 1];
     let data_slices: [&[u8]; 1] = [&data[..]];
 
@@ -17,29 +22,24 @@ pub fn enet_host_ping(host: &ENetHost, address: &ENetAddress) -> bool {
         dataLength: 1,
     };
 
-    if enet_socket_send(&host.socket, address, &data_slices, &[buffer], 1) > 0 {
-        true
-    } else {
-        false
-    }
+    enet_socket_send(&*host.socket, address, &data_slices, &[buffer], 1)?;
+
+    Ok(())
 }
 
 pub fn enet_host_create(
     address: &ENetAddress,
     peerCount: usize,
-    mut channelLimit: usize,
+    channelLimit: usize,
     incomingBandwidth: u32,
     outgoingBandwidth: u32,
     option: ENetHostOption,
-) -> Option<ENetHost> {
+) -> Result<ENetHost, ENetError> {
     if peerCount > ENET_PROTOCOL_MAXIMUM_PEER_ID as usize {
-        return None;
+        return Err(ENetError::ENET_ERROR_PEER_LIMIT);
     }
 
-    let socket = match enet_socket_create(address, option) {
-        Ok(x) => x,
-        Err(_) => return None,
-    };
+    let socket = enet_socket_create(address, option)?;
 
     let _ = enet_socket_set_option(&socket, ENetSocketOption::ENET_SOCKOPT_NONBLOCK, 1);
     let _ = enet_socket_set_option(&socket, ENetSocketOption::ENET_SOCKOPT_BROADCAST, 1);
@@ -56,6 +56,29 @@ pub fn enet_host_create(
         ENET_HOST_SEND_BUFFER_SIZE as i32,
     );
 
+    enet_host_create_with_transport(
+        Box::new(socket),
+        peerCount,
+        channelLimit,
+        incomingBandwidth,
+        outgoingBandwidth,
+    )
+}
+
+#[allow(clippy::nonminimal_bool, clippy::manual_rotate)]
+pub fn enet_host_create_with_transport(
+    socket: Box<dyn ENetTransport>,
+    peerCount: usize,
+    mut channelLimit: usize,
+    incomingBandwidth: u32,
+    outgoingBandwidth: u32,
+) -> Result<ENetHost, ENetError> {
+    if peerCount > ENET_PROTOCOL_MAXIMUM_PEER_ID as usize {
+        return Err(ENetError::ENET_ERROR_PEER_LIMIT);
+    }
+
+    let address = ENetAddress::from(socket.local_addr()?);
+
     if !(channelLimit != 0) || channelLimit > (ENET_PROTOCOL_MAXIMUM_CHANNEL_COUNT as usize) {
         channelLimit = ENET_PROTOCOL_MAXIMUM_CHANNEL_COUNT as usize;
     } else if channelLimit < ENET_PROTOCOL_MINIMUM_CHANNEL_COUNT as usize {
@@ -64,7 +87,7 @@ pub fn enet_host_create(
 
     let mut host = ENetHost {
         socket,
-        address: *address,
+        address,
         incomingBandwidth,
         outgoingBandwidth,
         bandwidthThrottleEpoch: 0,
@@ -82,8 +105,10 @@ pub fn enet_host_create(
         commandCount: 0,
         buffers: [ENetBuffer::default(); ENET_BUFFER_MAXIMUM as usize],
         bufferCount: 0,
+        bufferPackets: std::array::from_fn(|_| None),
         checksum: None,
         compressor: ENetCompressor::new(),
+        clock: Box::new(ENetSystemClock),
         packetData: [[0u8; ENET_PROTOCOL_MAXIMUM_MTU as usize]; 2],
         receivedAddress: ENetAddress::new(),
         receivedData: 0,
@@ -171,10 +196,18 @@ pub fn enet_host_create(
 
     host.peers = peers.into_boxed_slice();
 
-    Some(host)
+    for i in 0..peerCount {
+        enet_peer_reset(&mut host, i as u16);
+    }
+
+    Ok(host)
 }
 
-pub fn enet_host_destroy(host: ENetHost) {
+pub fn enet_host_destroy(mut host: ENetHost) {
+    for i in 0..host.peers.len() {
+        enet_peer_reset(&mut host, i as u16);
+    }
+
     if let Some(context) = host.compressor.context {
         if let Some(destroy) = host.compressor.destroy {
             destroy(Some(context));
@@ -182,9 +215,440 @@ pub fn enet_host_destroy(host: ENetHost) {
     }
 }
 
+pub fn enet_host_compress(host: &mut ENetHost, compressor: Option<ENetCompressor>) {
+    if let Some(context) = host.compressor.context.take() {
+        if let Some(destroy) = host.compressor.destroy {
+            destroy(Some(context));
+        }
+    }
+
+    host.compressor = compressor.unwrap_or_else(ENetCompressor::new);
+}
+
+pub fn enet_host_clock(host: &mut ENetHost, clock: Option<Box<dyn ENetClock>>) {
+    host.clock = clock.unwrap_or_else(|| Box::new(ENetSystemClock));
+}
+
+pub fn enet_host_check_events(
+    host: &mut ENetHost,
+    event: Option<&mut ENetEvent>,
+) -> Result<i32, ENetError> {
+    let event = match event {
+        Some(x) => x,
+        None => return Ok(0),
+    };
+
+    *event = ENetEvent::default();
+
+    Ok(enet_protocol_dispatch_incoming_commands(host, event))
+}
+
+pub fn enet_host_flush(host: &mut ENetHost) -> Result<(), ENetError> {
+    host.serviceTime = host.clock.now();
+
+    enet_protocol_send_outgoing_commands(host, None, 0)?;
+
+    Ok(())
+}
+
+#[allow(clippy::nonminimal_bool)]
+pub fn enet_host_service(
+    host: &mut ENetHost,
+    mut event: Option<&mut ENetEvent>,
+    timeout: u32,
+) -> Result<i32, ENetError> {
+    if let Some(event) = event.as_deref_mut() {
+        *event = ENetEvent::default();
+
+        if enet_protocol_dispatch_incoming_commands(host, event) == 1 {
+            return Ok(1);
+        }
+    }
+
+    host.serviceTime = host.clock.now();
+
+    let timeout = timeout.wrapping_add(host.serviceTime);
+
+    loop {
+        if ENET_TIME_DIFFERENCE(host.serviceTime, host.bandwidthThrottleEpoch)
+            >= ENET_HOST_BANDWIDTH_THROTTLE_INTERVAL
+        {
+            enet_host_bandwidth_throttle(host);
+        }
+
+        if enet_protocol_send_outgoing_commands(host, event.as_deref_mut(), 1)? == 1 {
+            return Ok(1);
+        }
+
+        if enet_protocol_receive_incoming_commands(host, event.as_deref_mut())? == 1 {
+            return Ok(1);
+        }
+
+        if enet_protocol_send_outgoing_commands(host, event.as_deref_mut(), 1)? == 1 {
+            return Ok(1);
+        }
+
+        if let Some(event) = event.as_deref_mut() {
+            if enet_protocol_dispatch_incoming_commands(host, event) == 1 {
+                return Ok(1);
+            }
+        }
+
+        if ENET_TIME_GREATER_EQUAL(host.serviceTime, timeout) {
+            return Ok(0);
+        }
+
+        let mut waitCondition;
+
+        loop {
+            host.serviceTime = host.clock.now();
+
+            if ENET_TIME_GREATER_EQUAL(host.serviceTime, timeout) {
+                return Ok(0);
+            }
+
+            waitCondition = ENET_SOCKET_WAIT_RECEIVE as u32 | ENET_SOCKET_WAIT_INTERRUPT as u32;
+
+            enet_socket_wait(
+                &*host.socket,
+                &mut waitCondition,
+                ENET_TIME_DIFFERENCE(timeout, host.serviceTime),
+            )?;
+
+            if !((waitCondition & ENET_SOCKET_WAIT_INTERRUPT as u32) != 0) {
+                break;
+            }
+        }
+
+        host.serviceTime = host.clock.now();
+
+        if !((waitCondition & ENET_SOCKET_WAIT_RECEIVE as u32) != 0) {
+            return Ok(0);
+        }
+    }
+}
+
+pub fn enet_host_next_deadline(host: &ENetHost) -> Option<u32> {
+    let timeCurrent = host.clock.now();
+    let mut deadline: Option<u32> = None;
+
+    let mut schedule = |time: u32| {
+        let time = if ENET_TIME_LESS(time, timeCurrent) {
+            timeCurrent
+        } else {
+            time
+        };
+
+        deadline = match deadline {
+            Some(deadline) if ENET_TIME_LESS_EQUAL(deadline, time) => Some(deadline),
+            _ => Some(time),
+        };
+    };
+
+    if !host.dispatchQueue.is_empty() {
+        schedule(timeCurrent);
+    }
+
+    if host.connectedPeers > 0 {
+        schedule(
+            host.bandwidthThrottleEpoch
+                .wrapping_add(ENET_HOST_BANDWIDTH_THROTTLE_INTERVAL),
+        );
+    }
+
+    for currentPeer in host.peers.iter() {
+        if currentPeer.state == ENetPeerState::ENET_PEER_STATE_DISCONNECTED
+            || currentPeer.state == ENetPeerState::ENET_PEER_STATE_ZOMBIE
+        {
+            continue;
+        }
+
+        if !currentPeer.acknowledgements.is_empty() {
+            schedule(timeCurrent);
+        }
+
+        if enet_protocol_has_sendable_commands(currentPeer) {
+            schedule(timeCurrent);
+        }
+
+        if !currentPeer.sentReliableCommands.is_empty() {
+            schedule(currentPeer.nextTimeout);
+        } else if currentPeer.state == ENetPeerState::ENET_PEER_STATE_CONNECTED {
+            schedule(
+                currentPeer
+                    .lastReceiveTime
+                    .wrapping_add(currentPeer.pingInterval),
+            );
+        }
+    }
+
+    deadline
+}
+
+pub fn enet_host_connect(
+    host: &mut ENetHost,
+    address: &ENetAddress,
+    mut channelCount: usize,
+    data: u32,
+) -> Result<u16, ENetError> {
+    if channelCount < ENET_PROTOCOL_MINIMUM_CHANNEL_COUNT as usize {
+        channelCount = ENET_PROTOCOL_MINIMUM_CHANNEL_COUNT as usize;
+    } else if channelCount > ENET_PROTOCOL_MAXIMUM_CHANNEL_COUNT as usize {
+        channelCount = ENET_PROTOCOL_MAXIMUM_CHANNEL_COUNT as usize;
+    }
+
+    address.try_parse_any_by_socket(&*host.socket)?;
+
+    let incomingPeerID = host
+        .peers
+        .iter()
+        .find(|currentPeer| currentPeer.state == ENetPeerState::ENET_PEER_STATE_DISCONNECTED)
+        .ok_or(ENetError::ENET_ERROR_PEER_LIMIT)?
+        .incomingPeerID;
+
+    let connectID = enet_host_random(host);
+    let hostMtu = host.mtu;
+    let hostIncomingBandwidth = host.incomingBandwidth;
+    let hostOutgoingBandwidth = host.outgoingBandwidth;
+    let currentPeer = enet_host_get_mut_peer!(host, incomingPeerID);
+
+    currentPeer.channels = (0..channelCount).map(|_| ENetChannel::new()).collect();
+    currentPeer.channelCount = channelCount;
+    currentPeer.state = ENetPeerState::ENET_PEER_STATE_CONNECTING;
+    currentPeer.address = *address;
+    currentPeer.connectID = connectID;
+    currentPeer.mtu = hostMtu;
+
+    if hostOutgoingBandwidth == 0 {
+        currentPeer.windowSize = ENET_PROTOCOL_MAXIMUM_WINDOW_SIZE;
+    } else {
+        currentPeer.windowSize = (hostOutgoingBandwidth / ENET_PEER_WINDOW_SIZE_SCALE)
+            * ENET_PROTOCOL_MINIMUM_WINDOW_SIZE;
+    }
+
+    currentPeer.windowSize = currentPeer.windowSize.clamp(
+        ENET_PROTOCOL_MINIMUM_WINDOW_SIZE,
+        ENET_PROTOCOL_MAXIMUM_WINDOW_SIZE,
+    );
+
+    let command = ENetProtocol::connect(ENetProtocolConnect {
+        header: ENetProtocolCommandHeader {
+            command: (ENetProtocolCommand::ENET_PROTOCOL_COMMAND_CONNECT as u32
+                | ENetProtocolFlag::ENET_PROTOCOL_COMMAND_FLAG_ACKNOWLEDGE as u32)
+                as u8,
+            channelID: 0xFF,
+            reliableSequenceNumber: 0,
+        },
+        outgoingPeerID: currentPeer.incomingPeerID,
+        incomingSessionID: currentPeer.incomingSessionID,
+        outgoingSessionID: currentPeer.outgoingSessionID,
+        mtu: currentPeer.mtu,
+        windowSize: currentPeer.windowSize,
+        channelCount: channelCount as u32,
+        incomingBandwidth: hostIncomingBandwidth,
+        outgoingBandwidth: hostOutgoingBandwidth,
+        packetThrottleInterval: currentPeer.packetThrottleInterval,
+        packetThrottleAcceleration: currentPeer.packetThrottleAcceleration,
+        packetThrottleDeceleration: currentPeer.packetThrottleDeceleration,
+        connectID: currentPeer.connectID,
+        data,
+    });
+
+    enet_peer_queue_outgoing_command(host, incomingPeerID, &command, None, 0, 0);
+
+    Ok(incomingPeerID)
+}
+
+pub fn enet_host_bandwidth_limit(
+    host: &mut ENetHost,
+    incomingBandwidth: u32,
+    outgoingBandwidth: u32,
+) {
+    host.incomingBandwidth = incomingBandwidth;
+    host.outgoingBandwidth = outgoingBandwidth;
+    host.recalculateBandwidthLimits = 1;
+}
+
+pub fn enet_host_bandwidth_throttle(host: &mut ENetHost) {
+    let timeCurrent = host.clock.now();
+    let elapsedTime = timeCurrent.wrapping_sub(host.bandwidthThrottleEpoch);
+    let mut peersRemaining = host.connectedPeers as u32;
+    let mut dataTotal = !0u32;
+    let mut bandwidth = !0u32;
+    let mut throttle;
+    let mut bandwidthLimit = 0u32;
+    let mut needsAdjustment = host.bandwidthLimitedPeers > 0;
+
+    if elapsedTime < ENET_HOST_BANDWIDTH_THROTTLE_INTERVAL {
+        return;
+    }
+
+    host.bandwidthThrottleEpoch = timeCurrent;
+
+    if peersRemaining == 0 {
+        return;
+    }
+
+    if host.outgoingBandwidth != 0 {
+        dataTotal = 0;
+        bandwidth = host.outgoingBandwidth.wrapping_mul(elapsedTime) / 1000;
+
+        for peer in host.peers.iter() {
+            if peer.state != ENetPeerState::ENET_PEER_STATE_CONNECTED
+                && peer.state != ENetPeerState::ENET_PEER_STATE_DISCONNECT_LATER
+            {
+                continue;
+            }
+
+            dataTotal = dataTotal.wrapping_add(peer.outgoingDataTotal);
+        }
+    }
+
+    while peersRemaining > 0 && needsAdjustment {
+        needsAdjustment = false;
+
+        if dataTotal <= bandwidth {
+            throttle = ENET_PEER_PACKET_THROTTLE_SCALE;
+        } else {
+            throttle = bandwidth.wrapping_mul(ENET_PEER_PACKET_THROTTLE_SCALE) / dataTotal;
+        }
+
+        for peer in host.peers.iter_mut() {
+            if (peer.state != ENetPeerState::ENET_PEER_STATE_CONNECTED
+                && peer.state != ENetPeerState::ENET_PEER_STATE_DISCONNECT_LATER)
+                || peer.incomingBandwidth == 0
+                || peer.outgoingBandwidthThrottleEpoch == timeCurrent
+            {
+                continue;
+            }
+
+            let peerBandwidth = peer.incomingBandwidth.wrapping_mul(elapsedTime) / 1000;
+            if throttle.wrapping_mul(peer.outgoingDataTotal) / ENET_PEER_PACKET_THROTTLE_SCALE
+                <= peerBandwidth
+            {
+                continue;
+            }
+
+            peer.packetThrottleLimit = peerBandwidth.wrapping_mul(ENET_PEER_PACKET_THROTTLE_SCALE)
+                / peer.outgoingDataTotal;
+
+            if peer.packetThrottleLimit == 0 {
+                peer.packetThrottleLimit = 1;
+            }
+
+            if peer.packetThrottle > peer.packetThrottleLimit {
+                peer.packetThrottle = peer.packetThrottleLimit;
+            }
+
+            peer.outgoingBandwidthThrottleEpoch = timeCurrent;
+
+            peer.incomingDataTotal = 0;
+            peer.outgoingDataTotal = 0;
+
+            needsAdjustment = true;
+            peersRemaining -= 1;
+            bandwidth = bandwidth.wrapping_sub(peerBandwidth);
+            dataTotal = dataTotal.wrapping_sub(peerBandwidth);
+        }
+    }
+
+    if peersRemaining > 0 {
+        if dataTotal <= bandwidth {
+            throttle = ENET_PEER_PACKET_THROTTLE_SCALE;
+        } else {
+            throttle = bandwidth.wrapping_mul(ENET_PEER_PACKET_THROTTLE_SCALE) / dataTotal;
+        }
+
+        for peer in host.peers.iter_mut() {
+            if (peer.state != ENetPeerState::ENET_PEER_STATE_CONNECTED
+                && peer.state != ENetPeerState::ENET_PEER_STATE_DISCONNECT_LATER)
+                || peer.outgoingBandwidthThrottleEpoch == timeCurrent
+            {
+                continue;
+            }
+
+            peer.packetThrottleLimit = throttle;
+
+            if peer.packetThrottle > peer.packetThrottleLimit {
+                peer.packetThrottle = peer.packetThrottleLimit;
+            }
+
+            peer.incomingDataTotal = 0;
+            peer.outgoingDataTotal = 0;
+        }
+    }
+
+    if host.recalculateBandwidthLimits != 0 {
+        host.recalculateBandwidthLimits = 0;
+
+        peersRemaining = host.connectedPeers as u32;
+        bandwidth = host.incomingBandwidth;
+        needsAdjustment = true;
+
+        if bandwidth == 0 {
+            bandwidthLimit = 0;
+        } else {
+            while peersRemaining > 0 && needsAdjustment {
+                needsAdjustment = false;
+                bandwidthLimit = bandwidth / peersRemaining;
+
+                for peer in host.peers.iter_mut() {
+                    if (peer.state != ENetPeerState::ENET_PEER_STATE_CONNECTED
+                        && peer.state != ENetPeerState::ENET_PEER_STATE_DISCONNECT_LATER)
+                        || peer.incomingBandwidthThrottleEpoch == timeCurrent
+                    {
+                        continue;
+                    }
+
+                    if peer.outgoingBandwidth > 0 && peer.outgoingBandwidth >= bandwidthLimit {
+                        continue;
+                    }
+
+                    peer.incomingBandwidthThrottleEpoch = timeCurrent;
+
+                    needsAdjustment = true;
+                    peersRemaining -= 1;
+                    bandwidth = bandwidth.wrapping_sub(peer.outgoingBandwidth);
+                }
+            }
+        }
+
+        for i in 0..host.peers.len() {
+            let incomingPeerID = i as u16;
+            let outgoingBandwidth = host.outgoingBandwidth;
+            let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+            if peer.state != ENetPeerState::ENET_PEER_STATE_CONNECTED
+                && peer.state != ENetPeerState::ENET_PEER_STATE_DISCONNECT_LATER
+            {
+                continue;
+            }
+
+            let command = ENetProtocol::bandwidthLimit(ENetProtocolBandwidthLimit {
+                header: ENetProtocolCommandHeader {
+                    command: (ENetProtocolCommand::ENET_PROTOCOL_COMMAND_BANDWIDTH_LIMIT as u32
+                        | ENetProtocolFlag::ENET_PROTOCOL_COMMAND_FLAG_ACKNOWLEDGE as u32)
+                        as u8,
+                    channelID: 0xFF,
+                    reliableSequenceNumber: 0,
+                },
+                incomingBandwidth: if peer.incomingBandwidthThrottleEpoch == timeCurrent {
+                    peer.outgoingBandwidth
+                } else {
+                    bandwidthLimit
+                },
+                outgoingBandwidth,
+            });
+
+            enet_peer_queue_outgoing_command(host, incomingPeerID, &command, None, 0, 0);
+        }
+    }
+}
+
 pub fn enet_host_random(host: &mut ENetHost) -> u32 {
-    let mut n = host.randomSeed.wrapping_add(0x6D2B79F5);
-    n = (n ^ (n >> 15)) * (n | 1);
-    n ^= n + (n ^ (n >> 7)) * (n | 61);
+    host.randomSeed = host.randomSeed.wrapping_add(0x6D2B79F5);
+
+    let mut n = host.randomSeed;
+    n = (n ^ (n >> 15)).wrapping_mul(n | 1);
+    n ^= n.wrapping_add((n ^ (n >> 7)).wrapping_mul(n | 61));
     n ^ (n >> 14)
 }
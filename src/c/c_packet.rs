@@ -10,6 +10,7 @@ use crate::h_win32::ENET_HOST_TO_NET_32;
 use std::cell::RefCell;
 use std::rc::Rc;
 
+#[allow(clippy::absurd_extreme_comparisons, clippy::len_zero)]
 pub fn enet_packet_create(data: Rc<RefCell<Vec<u8>>>, dataLength: usize, flags: u32) -> ENetPacket {
     let data = if (flags & ENET_PACKET_FLAG_NO_ALLOCATE as u32) != 0 {
         Some(Rc::clone(&data))
@@ -35,6 +36,17 @@ pub fn enet_packet_destroy(packet: ENetPacket) {
     }
 }
 
+pub fn enet_packet_release(packet: Rc<RefCell<ENetPacket>>, flags: u32) {
+    let mut packet = packet.borrow_mut();
+
+    packet.referenceCount -= 1;
+
+    if packet.referenceCount == 0 {
+        packet.flags |= flags;
+        enet_packet_destroy(std::mem::take(&mut *packet));
+    }
+}
+
 pub fn enet_packet_resize(packet: &mut ENetPacket, dataLength: usize) {
     if dataLength <= packet.dataLength
         || (packet.flags & (ENET_PACKET_FLAG_NO_ALLOCATE as u32) != 0)
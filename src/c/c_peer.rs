@@ -0,0 +1,1044 @@
+﻿#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(non_camel_case_types)]
+
+use crate::c_host::*;
+use crate::c_packet::*;
+use crate::c_protocol::*;
+use crate::enet_host_get_mut_peer;
+use crate::h_enet::ENetPacketFlag::*;
+use crate::h_enet::ENetPeerFlag::*;
+use crate::h_enet::ENetPeerState::*;
+use crate::h_enet::*;
+use crate::h_protocol::ENetProtocolCommand::*;
+use crate::h_protocol::ENetProtocolFlag::*;
+use crate::h_protocol::*;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+pub fn enet_peer_on_connect(host: &mut ENetHost, incomingPeerID: u16) {
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if peer.state != ENET_PEER_STATE_CONNECTED && peer.state != ENET_PEER_STATE_DISCONNECT_LATER {
+        if peer.incomingBandwidth != 0 {
+            host.bandwidthLimitedPeers += 1;
+        }
+
+        host.connectedPeers += 1;
+    }
+}
+
+pub fn enet_peer_on_disconnect(host: &mut ENetHost, incomingPeerID: u16) {
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if peer.state == ENET_PEER_STATE_CONNECTED || peer.state == ENET_PEER_STATE_DISCONNECT_LATER {
+        if peer.incomingBandwidth != 0 {
+            host.bandwidthLimitedPeers -= 1;
+        }
+
+        host.connectedPeers -= 1;
+    }
+}
+
+pub fn enet_peer_reset_outgoing_commands(queue: &mut VecDeque<ENetOutgoingCommand>) {
+    while let Some(outgoingCommand) = queue.pop_front() {
+        if let Some(packet) = outgoingCommand.packet {
+            enet_packet_release(packet, 0);
+        }
+    }
+}
+
+pub fn enet_peer_reset_queues(host: &mut ENetHost, incomingPeerID: u16) {
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if ((peer.flags as u32) & (ENET_PEER_FLAG_NEEDS_DISPATCH as u32)) != 0 {
+        host.dispatchQueue.retain(|&x| x != incomingPeerID);
+
+        let mut flags = peer.flags as u32;
+        flags &= !(ENET_PEER_FLAG_NEEDS_DISPATCH as u32);
+        peer.flags = flags as u16;
+    }
+
+    peer.acknowledgements.clear();
+
+    enet_peer_reset_outgoing_commands(&mut peer.sentReliableCommands);
+    enet_peer_reset_outgoing_commands(&mut peer.outgoingCommands);
+    enet_peer_reset_outgoing_commands(&mut peer.outgoingSendReliableCommands);
+    peer.dispatchedCommands.clear();
+
+    peer.channels.clear();
+    peer.channelCount = 0;
+}
+
+pub fn enet_peer_reset(host: &mut ENetHost, incomingPeerID: u16) {
+    enet_peer_on_disconnect(host, incomingPeerID);
+    enet_peer_reset_queues(host, incomingPeerID);
+
+    let mtu = host.mtu;
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+
+    peer.outgoingPeerID = ENET_PROTOCOL_MAXIMUM_PEER_ID as u16;
+    peer.connectID = 0;
+
+    peer.state = ENET_PEER_STATE_DISCONNECTED;
+
+    peer.incomingBandwidth = 0;
+    peer.outgoingBandwidth = 0;
+    peer.incomingBandwidthThrottleEpoch = 0;
+    peer.outgoingBandwidthThrottleEpoch = 0;
+    peer.incomingDataTotal = 0;
+    peer.outgoingDataTotal = 0;
+    peer.lastSendTime = 0;
+    peer.lastReceiveTime = 0;
+    peer.nextTimeout = 0;
+    peer.earliestTimeout = 0;
+    peer.packetLossEpoch = 0;
+    peer.packetsSent = 0;
+    peer.packetsLost = 0;
+    peer.packetLoss = 0;
+    peer.packetLossVariance = 0;
+    peer.packetThrottle = ENET_PEER_DEFAULT_PACKET_THROTTLE;
+    peer.packetThrottleLimit = ENET_PEER_PACKET_THROTTLE_SCALE;
+    peer.packetThrottleCounter = 0;
+    peer.packetThrottleEpoch = 0;
+    peer.packetThrottleAcceleration = ENET_PEER_PACKET_THROTTLE_ACCELERATION;
+    peer.packetThrottleDeceleration = ENET_PEER_PACKET_THROTTLE_DECELERATION;
+    peer.packetThrottleInterval = ENET_PEER_PACKET_THROTTLE_INTERVAL;
+    peer.pingInterval = ENET_PEER_PING_INTERVAL;
+    peer.timeoutLimit = ENET_PEER_TIMEOUT_LIMIT;
+    peer.timeoutMinimum = ENET_PEER_TIMEOUT_MINIMUM;
+    peer.timeoutMaximum = ENET_PEER_TIMEOUT_MAXIMUM;
+    peer.lastRoundTripTime = ENET_PEER_DEFAULT_ROUND_TRIP_TIME;
+    peer.lowestRoundTripTime = ENET_PEER_DEFAULT_ROUND_TRIP_TIME;
+    peer.lastRoundTripTimeVariance = 0;
+    peer.highestRoundTripTimeVariance = 0;
+    peer.roundTripTime = ENET_PEER_DEFAULT_ROUND_TRIP_TIME;
+    peer.roundTripTimeVariance = 0;
+    peer.mtu = mtu;
+    peer.reliableDataInTransit = 0;
+    peer.outgoingReliableSequenceNumber = 0;
+    peer.windowSize = ENET_PROTOCOL_MAXIMUM_WINDOW_SIZE;
+    peer.incomingUnsequencedGroup = 0;
+    peer.outgoingUnsequencedGroup = 0;
+    peer.eventData = 0;
+    peer.totalWaitingData = 0;
+    peer.flags = 0;
+
+    peer.unsequencedWindow.fill(0);
+}
+
+pub fn enet_peer_queue_acknowledgement(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    command: &ENetProtocol,
+    sentTime: u16,
+) {
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    let header = command.command_header();
+
+    if (header.channelID as usize) < peer.channelCount {
+        let channel = &peer.channels[header.channelID as usize];
+        let mut reliableWindow =
+            header.reliableSequenceNumber as u32 / ENET_PEER_RELIABLE_WINDOW_SIZE;
+        let currentWindow =
+            channel.incomingReliableSequenceNumber as u32 / ENET_PEER_RELIABLE_WINDOW_SIZE;
+
+        if header.reliableSequenceNumber < channel.incomingReliableSequenceNumber {
+            reliableWindow += ENET_PEER_RELIABLE_WINDOWS;
+        }
+
+        if reliableWindow >= currentWindow + ENET_PEER_FREE_RELIABLE_WINDOWS - 1
+            && reliableWindow <= currentWindow + ENET_PEER_FREE_RELIABLE_WINDOWS
+        {
+            return;
+        }
+    }
+
+    peer.outgoingDataTotal = peer
+        .outgoingDataTotal
+        .wrapping_add(enet_protocol_command_size(ENET_PROTOCOL_COMMAND_ACKNOWLEDGE as u8) as u32);
+
+    peer.acknowledgements.push_back(ENetAcknowledgement {
+        sentTime: sentTime as u32,
+        command: *command,
+    });
+}
+
+pub fn enet_peer_setup_outgoing_command(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    mut outgoingCommand: ENetOutgoingCommand,
+) {
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    let header = *outgoingCommand.command.command_header();
+
+    peer.outgoingDataTotal = peer.outgoingDataTotal.wrapping_add(
+        enet_protocol_command_size(header.command) as u32 + outgoingCommand.fragmentLength as u32,
+    );
+
+    if header.channelID == 0xFF {
+        peer.outgoingReliableSequenceNumber = peer.outgoingReliableSequenceNumber.wrapping_add(1);
+
+        outgoingCommand.reliableSequenceNumber = peer.outgoingReliableSequenceNumber;
+        outgoingCommand.unreliableSequenceNumber = 0;
+    } else {
+        let channel = &mut peer.channels[header.channelID as usize];
+
+        if ((header.command as u32) & (ENET_PROTOCOL_COMMAND_FLAG_ACKNOWLEDGE as u32)) != 0 {
+            channel.outgoingReliableSequenceNumber =
+                channel.outgoingReliableSequenceNumber.wrapping_add(1);
+            channel.outgoingUnreliableSequenceNumber = 0;
+
+            outgoingCommand.reliableSequenceNumber = channel.outgoingReliableSequenceNumber;
+            outgoingCommand.unreliableSequenceNumber = 0;
+        } else if ((header.command as u32) & (ENET_PROTOCOL_COMMAND_FLAG_UNSEQUENCED as u32)) != 0 {
+            peer.outgoingUnsequencedGroup = peer.outgoingUnsequencedGroup.wrapping_add(1);
+
+            outgoingCommand.reliableSequenceNumber = 0;
+            outgoingCommand.unreliableSequenceNumber = 0;
+        } else {
+            if outgoingCommand.fragmentOffset == 0 {
+                channel.outgoingUnreliableSequenceNumber =
+                    channel.outgoingUnreliableSequenceNumber.wrapping_add(1);
+            }
+
+            outgoingCommand.reliableSequenceNumber = channel.outgoingReliableSequenceNumber;
+            outgoingCommand.unreliableSequenceNumber = channel.outgoingUnreliableSequenceNumber;
+        }
+    }
+
+    outgoingCommand.sendAttempts = 0;
+    outgoingCommand.sentTime = 0;
+    outgoingCommand.roundTripTimeout = 0;
+    outgoingCommand
+        .command
+        .command_header_mut()
+        .reliableSequenceNumber = outgoingCommand.reliableSequenceNumber;
+
+    host.totalQueued = host.totalQueued.wrapping_add(1);
+    outgoingCommand.queueTime = host.totalQueued;
+
+    if let Some(packet) = &outgoingCommand.packet {
+        packet.borrow_mut().referenceCount += 1;
+    }
+
+    match &mut outgoingCommand.command {
+        ENetProtocol::sendUnreliable(sendUnreliable) => {
+            sendUnreliable.unreliableSequenceNumber = outgoingCommand.unreliableSequenceNumber;
+        }
+
+        ENetProtocol::sendUnsequenced(sendUnsequenced) => {
+            sendUnsequenced.unsequencedGroup = peer.outgoingUnsequencedGroup;
+        }
+
+        _ => {}
+    }
+
+    if ((header.command as u32) & (ENET_PROTOCOL_COMMAND_FLAG_ACKNOWLEDGE as u32)) != 0
+        && outgoingCommand.packet.is_some()
+    {
+        peer.outgoingSendReliableCommands.push_back(outgoingCommand);
+    } else {
+        peer.outgoingCommands.push_back(outgoingCommand);
+    }
+}
+
+pub fn enet_peer_queue_outgoing_command(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    command: &ENetProtocol,
+    packet: Option<Rc<RefCell<ENetPacket>>>,
+    offset: u32,
+    length: u16,
+) {
+    let outgoingCommand = ENetOutgoingCommand {
+        reliableSequenceNumber: 0,
+        unreliableSequenceNumber: 0,
+        sentTime: 0,
+        roundTripTimeout: 0,
+        queueTime: 0,
+        fragmentOffset: offset,
+        fragmentLength: length,
+        sendAttempts: 0,
+        command: *command,
+        packet,
+    };
+
+    enet_peer_setup_outgoing_command(host, incomingPeerID, outgoingCommand);
+}
+
+pub fn enet_peer_send(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    channelID: u8,
+    packet: ENetPacket,
+) -> Result<(), ENetError> {
+    let hasChecksum = host.checksum.is_some();
+    let maximumPacketSize = host.maximumPacketSize;
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+
+    if peer.state != ENET_PEER_STATE_CONNECTED {
+        return Err(ENetError::ENET_ERROR_NOT_CONNECTED);
+    }
+
+    if channelID as usize >= peer.channelCount {
+        return Err(ENetError::ENET_ERROR_INVALID_CHANNEL);
+    }
+
+    if packet.dataLength > maximumPacketSize {
+        return Err(ENetError::ENET_ERROR_PACKET_TOO_LARGE);
+    }
+
+    let channel = &peer.channels[channelID as usize];
+    let mut fragmentLength = peer.mtu as usize
+        - ENET_PROTOCOL_HEADER_SIZE
+        - enet_protocol_command_size(ENET_PROTOCOL_COMMAND_SEND_FRAGMENT as u8);
+    if hasChecksum {
+        fragmentLength -= size_of::<u32>();
+    }
+
+    if packet.dataLength > fragmentLength {
+        let fragmentCount = packet.dataLength.div_ceil(fragmentLength);
+
+        if fragmentCount > ENET_PROTOCOL_MAXIMUM_FRAGMENT_COUNT as usize {
+            return Err(ENetError::ENET_ERROR_PACKET_TOO_LARGE);
+        }
+
+        let (commandNumber, startSequenceNumber) = if (packet.flags
+            & (ENET_PACKET_FLAG_RELIABLE as u32 | ENET_PACKET_FLAG_UNRELIABLE_FRAGMENT as u32))
+            == ENET_PACKET_FLAG_UNRELIABLE_FRAGMENT as u32
+            && channel.outgoingUnreliableSequenceNumber < 0xFFFF
+        {
+            (
+                ENET_PROTOCOL_COMMAND_SEND_UNRELIABLE_FRAGMENT as u8,
+                channel.outgoingUnreliableSequenceNumber.wrapping_add(1),
+            )
+        } else {
+            (
+                (ENET_PROTOCOL_COMMAND_SEND_FRAGMENT as u32
+                    | ENET_PROTOCOL_COMMAND_FLAG_ACKNOWLEDGE as u32) as u8,
+                channel.outgoingReliableSequenceNumber.wrapping_add(1),
+            )
+        };
+
+        let totalLength = packet.dataLength;
+        let packet = Rc::new(RefCell::new(packet));
+        let mut fragments: VecDeque<ENetOutgoingCommand> = VecDeque::new();
+
+        let mut fragmentNumber = 0;
+        let mut fragmentOffset = 0;
+        while fragmentOffset < totalLength {
+            if totalLength - fragmentOffset < fragmentLength {
+                fragmentLength = totalLength - fragmentOffset;
+            }
+
+            fragments.push_back(ENetOutgoingCommand {
+                reliableSequenceNumber: 0,
+                unreliableSequenceNumber: 0,
+                sentTime: 0,
+                roundTripTimeout: 0,
+                queueTime: 0,
+                fragmentOffset: fragmentOffset as u32,
+                fragmentLength: fragmentLength as u16,
+                sendAttempts: 0,
+                command: ENetProtocol::sendFragment(ENetProtocolSendFragment {
+                    header: ENetProtocolCommandHeader {
+                        command: commandNumber,
+                        channelID,
+                        reliableSequenceNumber: 0,
+                    },
+                    startSequenceNumber,
+                    dataLength: fragmentLength as u16,
+                    fragmentCount: fragmentCount as u32,
+                    fragmentNumber,
+                    totalLength: totalLength as u32,
+                    fragmentOffset: fragmentOffset as u32,
+                }),
+                packet: Some(Rc::clone(&packet)),
+            });
+
+            fragmentNumber += 1;
+            fragmentOffset += fragmentLength;
+        }
+
+        while let Some(fragment) = fragments.pop_front() {
+            enet_peer_setup_outgoing_command(host, incomingPeerID, fragment);
+        }
+
+        return Ok(());
+    }
+
+    let header = ENetProtocolCommandHeader {
+        command: 0,
+        channelID,
+        reliableSequenceNumber: 0,
+    };
+
+    let command = if (packet.flags
+        & (ENET_PACKET_FLAG_RELIABLE as u32 | ENET_PACKET_FLAG_UNSEQUENCED as u32))
+        == ENET_PACKET_FLAG_UNSEQUENCED as u32
+    {
+        ENetProtocol::sendUnsequenced(ENetProtocolSendUnsequenced {
+            header: ENetProtocolCommandHeader {
+                command: (ENET_PROTOCOL_COMMAND_SEND_UNSEQUENCED as u32
+                    | ENET_PROTOCOL_COMMAND_FLAG_UNSEQUENCED as u32) as u8,
+                ..header
+            },
+            unsequencedGroup: 0,
+            dataLength: packet.dataLength as u16,
+        })
+    } else if (packet.flags & (ENET_PACKET_FLAG_RELIABLE as u32)) != 0
+        || channel.outgoingUnreliableSequenceNumber == 0xFFFF
+    {
+        ENetProtocol::sendReliable(ENetProtocolSendReliable {
+            header: ENetProtocolCommandHeader {
+                command: (ENET_PROTOCOL_COMMAND_SEND_RELIABLE as u32
+                    | ENET_PROTOCOL_COMMAND_FLAG_ACKNOWLEDGE as u32) as u8,
+                ..header
+            },
+            dataLength: packet.dataLength as u16,
+        })
+    } else {
+        ENetProtocol::sendUnreliable(ENetProtocolSendUnreliable {
+            header: ENetProtocolCommandHeader {
+                command: ENET_PROTOCOL_COMMAND_SEND_UNRELIABLE as u8,
+                ..header
+            },
+            unreliableSequenceNumber: 0,
+            dataLength: packet.dataLength as u16,
+        })
+    };
+
+    let dataLength = packet.dataLength as u16;
+    enet_peer_queue_outgoing_command(
+        host,
+        incomingPeerID,
+        &command,
+        Some(Rc::new(RefCell::new(packet))),
+        0,
+        dataLength,
+    );
+
+    Ok(())
+}
+
+pub fn enet_peer_receive(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    channelID: &mut u8,
+) -> Option<ENetPacket> {
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+
+    let incomingCommand = peer.dispatchedCommands.pop_front()?;
+
+    *channelID = incomingCommand.command.command_header().channelID;
+
+    let packet = incomingCommand.packet;
+
+    peer.totalWaitingData = peer.totalWaitingData.saturating_sub(packet.dataLength);
+
+    Some(packet)
+}
+
+fn enet_peer_remove_incoming_commands(
+    peer: &mut ENetPeer,
+    channelID: u8,
+    startCommand: usize,
+    endCommand: usize,
+    excludeCommand: Option<usize>,
+) {
+    let channel = &mut peer.channels[channelID as usize];
+
+    let mut currentCommand = startCommand;
+    let mut endCommand = endCommand;
+    while currentCommand < endCommand {
+        if Some(currentCommand) == excludeCommand {
+            currentCommand += 1;
+            continue;
+        }
+
+        let incomingCommand = channel.incomingUnreliableCommands.remove(currentCommand);
+        endCommand -= 1;
+
+        peer.totalWaitingData = peer
+            .totalWaitingData
+            .saturating_sub(incomingCommand.packet.dataLength);
+    }
+}
+
+fn enet_peer_move_incoming_commands(
+    dispatchedCommands: &mut VecDeque<ENetIncomingCommand>,
+    incomingCommands: &mut Vec<ENetIncomingCommand>,
+    startCommand: usize,
+    endCommand: usize,
+    excludeCommand: &mut Option<usize>,
+) {
+    dispatchedCommands.extend(incomingCommands.drain(startCommand..endCommand));
+
+    if let Some(exclude) = *excludeCommand {
+        if exclude >= endCommand {
+            *excludeCommand = Some(exclude - (endCommand - startCommand));
+        } else if exclude >= startCommand {
+            *excludeCommand = None;
+        }
+    }
+}
+
+#[allow(clippy::nonminimal_bool)]
+fn enet_peer_set_needs_dispatch(host: &mut ENetHost, incomingPeerID: u16) {
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if !(((peer.flags as u32) & (ENET_PEER_FLAG_NEEDS_DISPATCH as u32)) != 0) {
+        host.dispatchQueue.push_back(incomingPeerID);
+
+        let mut flags = peer.flags as u32;
+        flags |= ENET_PEER_FLAG_NEEDS_DISPATCH as u32;
+        peer.flags = flags as u16;
+    }
+}
+
+pub fn enet_peer_dispatch_incoming_unreliable_commands(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    channelID: u8,
+    mut queuedCommand: Option<usize>,
+) {
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    let channel = &mut peer.channels[channelID as usize];
+
+    let mut droppedCommand = 0;
+    let mut startCommand = 0;
+    let mut currentCommand = 0;
+    let mut needsDispatch = false;
+
+    while currentCommand < channel.incomingUnreliableCommands.len() {
+        let incomingCommand = &channel.incomingUnreliableCommands[currentCommand];
+
+        if ENetProtocolCommand::from(incomingCommand.command.command_header().command)
+            == ENET_PROTOCOL_COMMAND_SEND_UNSEQUENCED
+        {
+            currentCommand += 1;
+            continue;
+        }
+
+        if incomingCommand.reliableSequenceNumber == channel.incomingReliableSequenceNumber {
+            if incomingCommand.fragmentsRemaining == 0 {
+                channel.incomingUnreliableSequenceNumber = incomingCommand.unreliableSequenceNumber;
+
+                currentCommand += 1;
+                continue;
+            }
+
+            if startCommand != currentCommand {
+                enet_peer_move_incoming_commands(
+                    &mut peer.dispatchedCommands,
+                    &mut channel.incomingUnreliableCommands,
+                    startCommand,
+                    currentCommand,
+                    &mut queuedCommand,
+                );
+                currentCommand = startCommand;
+                needsDispatch = true;
+
+                droppedCommand = currentCommand;
+            } else if droppedCommand != currentCommand {
+                droppedCommand = currentCommand - 1;
+            }
+        } else {
+            let mut reliableWindow =
+                incomingCommand.reliableSequenceNumber as u32 / ENET_PEER_RELIABLE_WINDOW_SIZE;
+            let currentWindow =
+                channel.incomingReliableSequenceNumber as u32 / ENET_PEER_RELIABLE_WINDOW_SIZE;
+            if incomingCommand.reliableSequenceNumber < channel.incomingReliableSequenceNumber {
+                reliableWindow += ENET_PEER_RELIABLE_WINDOWS;
+            }
+            if reliableWindow >= currentWindow
+                && reliableWindow < currentWindow + ENET_PEER_FREE_RELIABLE_WINDOWS - 1
+            {
+                break;
+            }
+
+            if startCommand != currentCommand {
+                enet_peer_move_incoming_commands(
+                    &mut peer.dispatchedCommands,
+                    &mut channel.incomingUnreliableCommands,
+                    startCommand,
+                    currentCommand,
+                    &mut queuedCommand,
+                );
+                currentCommand = startCommand;
+                needsDispatch = true;
+            }
+
+            droppedCommand = currentCommand + 1;
+        }
+
+        currentCommand += 1;
+        startCommand = currentCommand;
+    }
+
+    if startCommand != currentCommand {
+        enet_peer_move_incoming_commands(
+            &mut peer.dispatchedCommands,
+            &mut channel.incomingUnreliableCommands,
+            startCommand,
+            currentCommand,
+            &mut queuedCommand,
+        );
+        currentCommand = startCommand;
+        needsDispatch = true;
+
+        droppedCommand = currentCommand;
+    }
+
+    enet_peer_remove_incoming_commands(peer, channelID, 0, droppedCommand, queuedCommand);
+
+    if needsDispatch {
+        enet_peer_set_needs_dispatch(host, incomingPeerID);
+    }
+}
+
+pub fn enet_peer_dispatch_incoming_reliable_commands(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    channelID: u8,
+) {
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    let channel = &mut peer.channels[channelID as usize];
+
+    let mut currentCommand = 0;
+    while currentCommand < channel.incomingReliableCommands.len() {
+        let incomingCommand = &channel.incomingReliableCommands[currentCommand];
+
+        if incomingCommand.fragmentsRemaining > 0
+            || incomingCommand.reliableSequenceNumber
+                != channel.incomingReliableSequenceNumber.wrapping_add(1)
+        {
+            break;
+        }
+
+        channel.incomingReliableSequenceNumber = incomingCommand.reliableSequenceNumber;
+
+        if incomingCommand.fragmentCount > 0 {
+            channel.incomingReliableSequenceNumber = channel
+                .incomingReliableSequenceNumber
+                .wrapping_add((incomingCommand.fragmentCount - 1) as u16);
+        }
+
+        currentCommand += 1;
+    }
+
+    if currentCommand == 0 {
+        return;
+    }
+
+    channel.incomingUnreliableSequenceNumber = 0;
+
+    peer.dispatchedCommands
+        .extend(channel.incomingReliableCommands.drain(..currentCommand));
+
+    let hasUnreliableCommands = !channel.incomingUnreliableCommands.is_empty();
+
+    enet_peer_set_needs_dispatch(host, incomingPeerID);
+
+    if hasUnreliableCommands {
+        enet_peer_dispatch_incoming_unreliable_commands(host, incomingPeerID, channelID, None);
+    }
+}
+
+pub fn enet_peer_queue_incoming_command(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    command: &ENetProtocol,
+    data: Option<Vec<u8>>,
+    dataLength: usize,
+    flags: u32,
+    fragmentCount: u32,
+) -> i32 {
+    let maximumWaitingData = host.maximumWaitingData;
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    let header = *command.command_header();
+    let channel = &mut peer.channels[header.channelID as usize];
+    let commandNumber = ENetProtocolCommand::from(header.command);
+
+    let mut unreliableSequenceNumber = 0;
+    let mut reliableSequenceNumber = 0;
+
+    let discardCommand = if fragmentCount > 0 { -1 } else { 0 };
+
+    if peer.state == ENET_PEER_STATE_DISCONNECT_LATER {
+        return discardCommand;
+    }
+
+    if commandNumber != ENET_PROTOCOL_COMMAND_SEND_UNSEQUENCED {
+        reliableSequenceNumber = header.reliableSequenceNumber;
+        let mut reliableWindow = reliableSequenceNumber as u32 / ENET_PEER_RELIABLE_WINDOW_SIZE;
+        let currentWindow =
+            channel.incomingReliableSequenceNumber as u32 / ENET_PEER_RELIABLE_WINDOW_SIZE;
+
+        if reliableSequenceNumber < channel.incomingReliableSequenceNumber {
+            reliableWindow += ENET_PEER_RELIABLE_WINDOWS;
+        }
+
+        if reliableWindow < currentWindow
+            || reliableWindow >= currentWindow + ENET_PEER_FREE_RELIABLE_WINDOWS - 1
+        {
+            return discardCommand;
+        }
+    }
+
+    let insertPosition = match commandNumber {
+        ENET_PROTOCOL_COMMAND_SEND_FRAGMENT | ENET_PROTOCOL_COMMAND_SEND_RELIABLE => {
+            if reliableSequenceNumber == channel.incomingReliableSequenceNumber {
+                return discardCommand;
+            }
+
+            let mut currentCommand = channel.incomingReliableCommands.len();
+            while currentCommand > 0 {
+                let incomingCommand = &channel.incomingReliableCommands[currentCommand - 1];
+
+                if reliableSequenceNumber >= channel.incomingReliableSequenceNumber {
+                    if incomingCommand.reliableSequenceNumber
+                        < channel.incomingReliableSequenceNumber
+                    {
+                        currentCommand -= 1;
+                        continue;
+                    }
+                } else if incomingCommand.reliableSequenceNumber
+                    >= channel.incomingReliableSequenceNumber
+                {
+                    break;
+                }
+
+                if incomingCommand.reliableSequenceNumber <= reliableSequenceNumber {
+                    if incomingCommand.reliableSequenceNumber < reliableSequenceNumber {
+                        break;
+                    }
+
+                    return discardCommand;
+                }
+
+                currentCommand -= 1;
+            }
+
+            currentCommand
+        }
+
+        ENET_PROTOCOL_COMMAND_SEND_UNRELIABLE | ENET_PROTOCOL_COMMAND_SEND_UNRELIABLE_FRAGMENT => {
+            unreliableSequenceNumber = match command {
+                ENetProtocol::sendUnreliable(sendUnreliable) => {
+                    sendUnreliable.unreliableSequenceNumber
+                }
+
+                ENetProtocol::sendFragment(sendFragment) => sendFragment.startSequenceNumber,
+
+                _ => return discardCommand,
+            };
+
+            if reliableSequenceNumber == channel.incomingReliableSequenceNumber
+                && unreliableSequenceNumber <= channel.incomingUnreliableSequenceNumber
+            {
+                return discardCommand;
+            }
+
+            let mut currentCommand = channel.incomingUnreliableCommands.len();
+            while currentCommand > 0 {
+                let incomingCommand = &channel.incomingUnreliableCommands[currentCommand - 1];
+
+                if reliableSequenceNumber >= channel.incomingReliableSequenceNumber {
+                    if incomingCommand.reliableSequenceNumber
+                        < channel.incomingReliableSequenceNumber
+                    {
+                        currentCommand -= 1;
+                        continue;
+                    }
+                } else if incomingCommand.reliableSequenceNumber
+                    >= channel.incomingReliableSequenceNumber
+                {
+                    break;
+                }
+
+                if incomingCommand.reliableSequenceNumber < reliableSequenceNumber {
+                    break;
+                }
+
+                if incomingCommand.reliableSequenceNumber > reliableSequenceNumber {
+                    currentCommand -= 1;
+                    continue;
+                }
+
+                if incomingCommand.unreliableSequenceNumber <= unreliableSequenceNumber {
+                    if incomingCommand.unreliableSequenceNumber < unreliableSequenceNumber {
+                        break;
+                    }
+
+                    return discardCommand;
+                }
+
+                currentCommand -= 1;
+            }
+
+            currentCommand
+        }
+
+        ENET_PROTOCOL_COMMAND_SEND_UNSEQUENCED => 0,
+
+        _ => return discardCommand,
+    };
+
+    if peer.totalWaitingData >= maximumWaitingData {
+        return -1;
+    }
+
+    let packet = ENetPacket {
+        referenceCount: 0,
+        flags,
+        data: Some(Rc::new(RefCell::new(
+            data.unwrap_or_else(|| vec![0u8; dataLength]),
+        ))),
+        dataLength,
+        freeCallback: None,
+        userData: None,
+    };
+
+    let fragments: VecDeque<u32> = if fragmentCount > 0 {
+        if fragmentCount > ENET_PROTOCOL_MAXIMUM_FRAGMENT_COUNT {
+            return -1;
+        }
+
+        vec![0u32; fragmentCount.div_ceil(32) as usize].into()
+    } else {
+        VecDeque::new()
+    };
+
+    peer.totalWaitingData += packet.dataLength;
+
+    let incomingCommand = ENetIncomingCommand {
+        reliableSequenceNumber: header.reliableSequenceNumber,
+        unreliableSequenceNumber,
+        command: *command,
+        fragmentCount,
+        fragmentsRemaining: fragmentCount,
+        fragments,
+        packet,
+    };
+
+    match commandNumber {
+        ENET_PROTOCOL_COMMAND_SEND_FRAGMENT | ENET_PROTOCOL_COMMAND_SEND_RELIABLE => {
+            channel
+                .incomingReliableCommands
+                .insert(insertPosition, incomingCommand);
+
+            enet_peer_dispatch_incoming_reliable_commands(host, incomingPeerID, header.channelID);
+        }
+
+        _ => {
+            channel
+                .incomingUnreliableCommands
+                .insert(insertPosition, incomingCommand);
+
+            enet_peer_dispatch_incoming_unreliable_commands(
+                host,
+                incomingPeerID,
+                header.channelID,
+                Some(insertPosition),
+            );
+        }
+    }
+
+    0
+}
+
+pub fn enet_peer_disconnect_now(host: &mut ENetHost, incomingPeerID: u16, data: u32) {
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if peer.state == ENET_PEER_STATE_DISCONNECTED {
+        return;
+    }
+
+    if peer.state != ENET_PEER_STATE_ZOMBIE && peer.state != ENET_PEER_STATE_DISCONNECTING {
+        enet_peer_reset_queues(host, incomingPeerID);
+
+        let command = ENetProtocol::disconnect(ENetProtocolDisconnect {
+            header: ENetProtocolCommandHeader {
+                command: (ENET_PROTOCOL_COMMAND_DISCONNECT as u32
+                    | ENET_PROTOCOL_COMMAND_FLAG_UNSEQUENCED as u32) as u8,
+                channelID: 0xFF,
+                reliableSequenceNumber: 0,
+            },
+            data,
+        });
+
+        enet_peer_queue_outgoing_command(host, incomingPeerID, &command, None, 0, 0);
+
+        let _ = enet_host_flush(host);
+    }
+
+    enet_peer_reset(host, incomingPeerID);
+}
+
+pub fn enet_peer_disconnect(host: &mut ENetHost, incomingPeerID: u16, data: u32) {
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if peer.state == ENET_PEER_STATE_DISCONNECTING
+        || peer.state == ENET_PEER_STATE_DISCONNECTED
+        || peer.state == ENET_PEER_STATE_ACKNOWLEDGING_DISCONNECT
+        || peer.state == ENET_PEER_STATE_ZOMBIE
+    {
+        return;
+    }
+
+    enet_peer_reset_queues(host, incomingPeerID);
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    let isConnected =
+        peer.state == ENET_PEER_STATE_CONNECTED || peer.state == ENET_PEER_STATE_DISCONNECT_LATER;
+
+    let mut commandNumber = ENET_PROTOCOL_COMMAND_DISCONNECT as u32;
+    if isConnected {
+        commandNumber |= ENET_PROTOCOL_COMMAND_FLAG_ACKNOWLEDGE as u32;
+    } else {
+        commandNumber |= ENET_PROTOCOL_COMMAND_FLAG_UNSEQUENCED as u32;
+    }
+
+    let command = ENetProtocol::disconnect(ENetProtocolDisconnect {
+        header: ENetProtocolCommandHeader {
+            command: commandNumber as u8,
+            channelID: 0xFF,
+            reliableSequenceNumber: 0,
+        },
+        data,
+    });
+
+    enet_peer_queue_outgoing_command(host, incomingPeerID, &command, None, 0, 0);
+
+    if isConnected {
+        enet_peer_on_disconnect(host, incomingPeerID);
+
+        enet_host_get_mut_peer!(host, incomingPeerID).state = ENET_PEER_STATE_DISCONNECTING;
+    } else {
+        let _ = enet_host_flush(host);
+        enet_peer_reset(host, incomingPeerID);
+    }
+}
+
+pub fn enet_peer_has_outgoing_commands(peer: &ENetPeer) -> bool {
+    !(peer.outgoingCommands.is_empty()
+        && peer.outgoingSendReliableCommands.is_empty()
+        && peer.sentReliableCommands.is_empty())
+}
+
+pub fn enet_peer_disconnect_later(host: &mut ENetHost, incomingPeerID: u16, data: u32) {
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if (peer.state == ENET_PEER_STATE_CONNECTED || peer.state == ENET_PEER_STATE_DISCONNECT_LATER)
+        && enet_peer_has_outgoing_commands(peer)
+    {
+        peer.state = ENET_PEER_STATE_DISCONNECT_LATER;
+        peer.eventData = data;
+    } else {
+        enet_peer_disconnect(host, incomingPeerID, data);
+    }
+}
+
+pub fn enet_peer_ping(host: &mut ENetHost, incomingPeerID: u16) {
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if peer.state != ENET_PEER_STATE_CONNECTED {
+        return;
+    }
+
+    let command = ENetProtocol::ping(ENetProtocolPing {
+        header: ENetProtocolCommandHeader {
+            command: (ENET_PROTOCOL_COMMAND_PING as u32
+                | ENET_PROTOCOL_COMMAND_FLAG_ACKNOWLEDGE as u32) as u8,
+            channelID: 0xFF,
+            reliableSequenceNumber: 0,
+        },
+    });
+
+    enet_peer_queue_outgoing_command(host, incomingPeerID, &command, None, 0, 0);
+}
+
+pub fn enet_peer_ping_interval(host: &mut ENetHost, incomingPeerID: u16, pingInterval: u32) {
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+
+    peer.pingInterval = if pingInterval != 0 {
+        pingInterval
+    } else {
+        ENET_PEER_PING_INTERVAL
+    };
+}
+
+pub fn enet_peer_timeout(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    timeoutLimit: u32,
+    timeoutMinimum: u32,
+    timeoutMaximum: u32,
+) {
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+
+    peer.timeoutLimit = if timeoutLimit != 0 {
+        timeoutLimit
+    } else {
+        ENET_PEER_TIMEOUT_LIMIT
+    };
+    peer.timeoutMinimum = if timeoutMinimum != 0 {
+        timeoutMinimum
+    } else {
+        ENET_PEER_TIMEOUT_MINIMUM
+    };
+    peer.timeoutMaximum = if timeoutMaximum != 0 {
+        timeoutMaximum
+    } else {
+        ENET_PEER_TIMEOUT_MAXIMUM
+    };
+}
+
+pub fn enet_peer_throttle_configure(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    interval: u32,
+    acceleration: u32,
+    deceleration: u32,
+) {
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+
+    peer.packetThrottleInterval = interval;
+    peer.packetThrottleAcceleration = acceleration;
+    peer.packetThrottleDeceleration = deceleration;
+
+    let command = ENetProtocol::throttleConfigure(ENetProtocolThrottleConfigure {
+        header: ENetProtocolCommandHeader {
+            command: (ENET_PROTOCOL_COMMAND_THROTTLE_CONFIGURE as u32
+                | ENET_PROTOCOL_COMMAND_FLAG_ACKNOWLEDGE as u32) as u8,
+            channelID: 0xFF,
+            reliableSequenceNumber: 0,
+        },
+        packetThrottleInterval: interval,
+        packetThrottleAcceleration: acceleration,
+        packetThrottleDeceleration: deceleration,
+    });
+
+    enet_peer_queue_outgoing_command(host, incomingPeerID, &command, None, 0, 0);
+}
+
+pub fn enet_peer_throttle(peer: &mut ENetPeer, rtt: u32) -> i32 {
+    if peer.lastRoundTripTime <= peer.lastRoundTripTimeVariance {
+        peer.packetThrottle = peer.packetThrottleLimit;
+    } else if rtt <= peer.lastRoundTripTime {
+        peer.packetThrottle = peer
+            .packetThrottle
+            .saturating_add(peer.packetThrottleAcceleration);
+
+        if peer.packetThrottle > peer.packetThrottleLimit {
+            peer.packetThrottle = peer.packetThrottleLimit;
+        }
+
+        return 1;
+    } else if rtt > peer.lastRoundTripTime + 2 * peer.lastRoundTripTimeVariance {
+        if peer.packetThrottle > peer.packetThrottleDeceleration {
+            peer.packetThrottle -= peer.packetThrottleDeceleration;
+        } else {
+            peer.packetThrottle = 0;
+        }
+
+        return -1;
+    }
+
+    0
+}
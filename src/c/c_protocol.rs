@@ -2,37 +2,58 @@
 #![allow(non_upper_case_globals)]
 #![allow(non_camel_case_types)]
 
+use crate::c_packet::*;
+use crate::c_peer::*;
 use crate::enet_host_get_mut_peer;
+use crate::h_enet::ENetPacketFlag::*;
 use crate::h_enet::ENetPeerFlag::*;
+use crate::h_enet::ENetPeerState::*;
 use crate::h_enet::*;
 use crate::h_protocol::ENetProtocolCommand::*;
+use crate::h_protocol::ENetProtocolError::*;
+use crate::h_protocol::ENetProtocolFlag::*;
 use crate::h_protocol::*;
+use crate::h_time::*;
+use crate::h_utility::*;
+use crate::h_win32::*;
+use std::cell::Ref;
 use std::collections::VecDeque;
 
 pub const commandSizes: [usize; ENET_PROTOCOL_COMMAND_COUNT as usize] = [
     0,
-    size_of::<ENetProtocolAcknowledge>(),
-    size_of::<ENetProtocolConnect>(),
-    size_of::<ENetProtocolVerifyConnect>(),
-    size_of::<ENetProtocolDisconnect>(),
-    size_of::<ENetProtocolPing>(),
-    size_of::<ENetProtocolSendReliable>(),
-    size_of::<ENetProtocolSendUnreliable>(),
-    size_of::<ENetProtocolSendFragment>(),
-    size_of::<ENetProtocolSendUnsequenced>(),
-    size_of::<ENetProtocolBandwidthLimit>(),
-    size_of::<ENetProtocolThrottleConfigure>(),
-    size_of::<ENetProtocolSendFragment>(),
+    ENET_PROTOCOL_ACKNOWLEDGE_SIZE,
+    ENET_PROTOCOL_CONNECT_SIZE,
+    ENET_PROTOCOL_VERIFY_CONNECT_SIZE,
+    ENET_PROTOCOL_DISCONNECT_SIZE,
+    ENET_PROTOCOL_PING_SIZE,
+    ENET_PROTOCOL_SEND_RELIABLE_SIZE,
+    ENET_PROTOCOL_SEND_UNRELIABLE_SIZE,
+    ENET_PROTOCOL_SEND_FRAGMENT_SIZE,
+    ENET_PROTOCOL_SEND_UNSEQUENCED_SIZE,
+    ENET_PROTOCOL_BANDWIDTH_LIMIT_SIZE,
+    ENET_PROTOCOL_THROTTLE_CONFIGURE_SIZE,
+    ENET_PROTOCOL_SEND_FRAGMENT_SIZE,
 ];
 
 pub fn enet_protocol_command_size(commandNumber: u8) -> usize {
-    commandSizes[((commandNumber as i32) & (ENET_PROTOCOL_COMMAND_MASK as i32)) as usize]
+    commandSizes
+        .get(((commandNumber as i32) & (ENET_PROTOCOL_COMMAND_MASK as i32)) as usize)
+        .copied()
+        .unwrap_or(0)
 }
 
 pub fn enet_protocol_change_state(host: &mut ENetHost, incomingPeerID: u16, state: ENetPeerState) {
-    todo!()
+    if state == ENET_PEER_STATE_CONNECTED || state == ENET_PEER_STATE_DISCONNECT_LATER {
+        enet_peer_on_connect(host, incomingPeerID);
+    } else {
+        enet_peer_on_disconnect(host, incomingPeerID);
+    }
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    peer.state = state;
 }
 
+#[allow(clippy::nonminimal_bool)]
 pub fn enet_protocol_dispatch_state(
     host: &mut ENetHost,
     incomingPeerID: u16,
@@ -49,24 +70,137 @@ pub fn enet_protocol_dispatch_state(
     }
 }
 
-pub fn enet_protocol_dispatch_incoming_commands(host: &ENetHost, event: &mut ENetEvent) {
-    todo!()
+pub fn enet_protocol_dispatch_incoming_commands(host: &mut ENetHost, event: &mut ENetEvent) -> i32 {
+    while let Some(incomingPeerID) = host.dispatchQueue.pop_front() {
+        let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+
+        let mut flags = peer.flags as u32;
+        flags &= !(ENET_PEER_FLAG_NEEDS_DISPATCH as u32);
+        peer.flags = flags as u16;
+
+        match peer.state {
+            ENET_PEER_STATE_CONNECTION_PENDING | ENET_PEER_STATE_CONNECTION_SUCCEEDED => {
+                enet_protocol_change_state(host, incomingPeerID, ENET_PEER_STATE_CONNECTED);
+
+                let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+                *event = ENetEvent::CONNECT {
+                    peer: incomingPeerID,
+                    channelID: 0,
+                    data: peer.eventData,
+                };
+
+                return 1;
+            }
+
+            ENET_PEER_STATE_ZOMBIE => {
+                host.recalculateBandwidthLimits = 1;
+
+                *event = ENetEvent::DISCONNECT {
+                    peer: incomingPeerID,
+                    channelID: 0,
+                    data: peer.eventData,
+                };
+
+                enet_peer_reset(host, incomingPeerID);
+
+                return 1;
+            }
+
+            ENET_PEER_STATE_CONNECTED => {
+                if peer.dispatchedCommands.is_empty() {
+                    continue;
+                }
+
+                let mut channelID = 0;
+                let packet = match enet_peer_receive(host, incomingPeerID, &mut channelID) {
+                    Some(x) => x,
+                    None => continue,
+                };
+
+                *event = ENetEvent::RECEIVE {
+                    peer: incomingPeerID,
+                    channelID,
+                    data: 0,
+                    packet,
+                };
+
+                let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+                if !peer.dispatchedCommands.is_empty() {
+                    let mut flags = peer.flags as u32;
+                    flags |= ENET_PEER_FLAG_NEEDS_DISPATCH as u32;
+                    peer.flags = flags as u16;
+
+                    host.dispatchQueue.push_back(incomingPeerID);
+                }
+
+                return 1;
+            }
+
+            _ => {}
+        }
+    }
+
+    0
 }
 
 pub fn enet_protocol_notify_connect(
     host: &mut ENetHost,
     incomingPeerID: u16,
-    event: &mut ENetEvent,
+    event: Option<&mut ENetEvent>,
 ) {
-    todo!()
+    host.recalculateBandwidthLimits = 1;
+
+    match event {
+        Some(event) => {
+            enet_protocol_change_state(host, incomingPeerID, ENET_PEER_STATE_CONNECTED);
+
+            let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+            *event = ENetEvent::CONNECT {
+                peer: incomingPeerID,
+                channelID: 0,
+                data: peer.eventData,
+            };
+        }
+
+        None => {
+            let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+            let state = if peer.state == ENET_PEER_STATE_CONNECTING {
+                ENET_PEER_STATE_CONNECTION_SUCCEEDED
+            } else {
+                ENET_PEER_STATE_CONNECTION_PENDING
+            };
+
+            enet_protocol_dispatch_state(host, incomingPeerID, state);
+        }
+    }
 }
 
 pub fn enet_protocol_notify_disconnect(
     host: &mut ENetHost,
     incomingPeerID: u16,
-    event: &mut ENetEvent,
+    event: Option<&mut ENetEvent>,
 ) {
-    todo!()
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if peer.state >= ENET_PEER_STATE_CONNECTION_PENDING {
+        host.recalculateBandwidthLimits = 1;
+    }
+
+    if peer.state != ENET_PEER_STATE_CONNECTING && peer.state < ENET_PEER_STATE_CONNECTION_SUCCEEDED
+    {
+        enet_peer_reset(host, incomingPeerID);
+    } else if let Some(event) = event {
+        *event = ENetEvent::DISCONNECT {
+            peer: incomingPeerID,
+            channelID: 0,
+            data: 0,
+        };
+
+        enet_peer_reset(host, incomingPeerID);
+    } else {
+        peer.eventData = 0;
+
+        enet_protocol_dispatch_state(host, incomingPeerID, ENET_PEER_STATE_ZOMBIE);
+    }
 }
 
 pub fn enet_protocol_remove_sent_unreliable_commands(
@@ -74,15 +208,47 @@ pub fn enet_protocol_remove_sent_unreliable_commands(
     incomingPeerID: u16,
     sentUnreliableCommands: &mut VecDeque<ENetOutgoingCommand>,
 ) {
-    todo!()
+    if sentUnreliableCommands.is_empty() {
+        return;
+    }
+
+    while let Some(outgoingCommand) = sentUnreliableCommands.pop_front() {
+        if let Some(packet) = outgoingCommand.packet {
+            enet_packet_release(packet, ENET_PACKET_FLAG_SENT as u32);
+        }
+    }
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if peer.state == ENET_PEER_STATE_DISCONNECT_LATER && !enet_peer_has_outgoing_commands(peer) {
+        let eventData = peer.eventData;
+        enet_peer_disconnect(host, incomingPeerID, eventData);
+    }
 }
 
+#[allow(clippy::nonminimal_bool)]
 pub fn enet_protocol_find_sent_reliable_command(
     list: &VecDeque<ENetOutgoingCommand>,
     reliableSequenceNumber: u16,
     channelID: u8,
 ) -> Option<usize> {
-    todo!()
+    for (i, outgoingCommand) in list.iter().enumerate() {
+        let header = outgoingCommand.command.command_header();
+        if !(((header.command as u32) & (ENET_PROTOCOL_COMMAND_FLAG_ACKNOWLEDGE as u32)) != 0) {
+            continue;
+        }
+
+        if outgoingCommand.sendAttempts < 1 {
+            break;
+        }
+
+        if outgoingCommand.reliableSequenceNumber == reliableSequenceNumber
+            && header.channelID == channelID
+        {
+            return Some(i);
+        }
+    }
+
+    None
 }
 
 pub fn enet_protocol_remove_sent_reliable_command(
@@ -91,5 +257,2335 @@ pub fn enet_protocol_remove_sent_reliable_command(
     reliableSequenceNumber: u16,
     channelID: u8,
 ) -> ENetProtocolCommand {
-    todo!()
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+
+    let position = peer
+        .sentReliableCommands
+        .iter()
+        .position(|outgoingCommand| {
+            outgoingCommand.reliableSequenceNumber == reliableSequenceNumber
+                && outgoingCommand.command.command_header().channelID == channelID
+        });
+
+    let (outgoingCommand, wasSent) = match position {
+        Some(i) => (peer.sentReliableCommands.remove(i), true),
+        None => {
+            if let Some(i) = enet_protocol_find_sent_reliable_command(
+                &peer.outgoingCommands,
+                reliableSequenceNumber,
+                channelID,
+            ) {
+                (peer.outgoingCommands.remove(i), false)
+            } else if let Some(i) = enet_protocol_find_sent_reliable_command(
+                &peer.outgoingSendReliableCommands,
+                reliableSequenceNumber,
+                channelID,
+            ) {
+                (peer.outgoingSendReliableCommands.remove(i), false)
+            } else {
+                (None, false)
+            }
+        }
+    };
+
+    let outgoingCommand = match outgoingCommand {
+        Some(x) => x,
+        None => return ENET_PROTOCOL_COMMAND_NONE,
+    };
+
+    if (channelID as usize) < peer.channelCount {
+        let channel = &mut peer.channels[channelID as usize];
+        let reliableWindow =
+            (reliableSequenceNumber as u32 / ENET_PEER_RELIABLE_WINDOW_SIZE) as usize;
+        if channel.reliableWindows[reliableWindow] > 0 {
+            channel.reliableWindows[reliableWindow] -= 1;
+            if channel.reliableWindows[reliableWindow] == 0 {
+                channel.usedReliableWindows &= !(1 << reliableWindow);
+            }
+        }
+    }
+
+    let commandNumber = ENetProtocolCommand::from(outgoingCommand.command.command_header().command);
+
+    if let Some(packet) = outgoingCommand.packet {
+        if wasSent {
+            peer.reliableDataInTransit -= outgoingCommand.fragmentLength as u32;
+        }
+
+        enet_packet_release(packet, ENET_PACKET_FLAG_SENT as u32);
+    }
+
+    if let Some(outgoingCommand) = peer.sentReliableCommands.front() {
+        peer.nextTimeout = outgoingCommand
+            .sentTime
+            .wrapping_add(outgoingCommand.roundTripTimeout);
+    }
+
+    commandNumber
+}
+
+pub fn enet_protocol_handle_acknowledge(
+    host: &mut ENetHost,
+    event: Option<&mut ENetEvent>,
+    incomingPeerID: u16,
+    command: &ENetProtocol,
+) -> i32 {
+    let acknowledge = match command {
+        ENetProtocol::acknowledge(x) => x,
+        _ => return -1,
+    };
+
+    let serviceTime = host.serviceTime;
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if peer.state == ENET_PEER_STATE_DISCONNECTED || peer.state == ENET_PEER_STATE_ZOMBIE {
+        return 0;
+    }
+
+    let mut receivedSentTime = acknowledge.receivedSentTime as u32;
+    receivedSentTime |= serviceTime & 0xFFFF0000;
+    if (receivedSentTime & 0x8000) > (serviceTime & 0x8000) {
+        receivedSentTime = receivedSentTime.wrapping_sub(0x10000);
+    }
+
+    if ENET_TIME_LESS(serviceTime, receivedSentTime) {
+        return 0;
+    }
+
+    let roundTripTime = ENET_TIME_DIFFERENCE(serviceTime, receivedSentTime).max(1);
+
+    if peer.lastReceiveTime > 0 {
+        enet_peer_throttle(peer, roundTripTime);
+
+        peer.roundTripTimeVariance -= peer.roundTripTimeVariance / 4;
+
+        if roundTripTime >= peer.roundTripTime {
+            let diff = roundTripTime - peer.roundTripTime;
+            peer.roundTripTimeVariance += diff / 4;
+            peer.roundTripTime += diff / 8;
+        } else {
+            let diff = peer.roundTripTime - roundTripTime;
+            peer.roundTripTimeVariance += diff / 4;
+            peer.roundTripTime -= diff / 8;
+        }
+    } else {
+        peer.roundTripTime = roundTripTime;
+        peer.roundTripTimeVariance = roundTripTime.div_ceil(2);
+    }
+
+    if peer.roundTripTime < peer.lowestRoundTripTime {
+        peer.lowestRoundTripTime = peer.roundTripTime;
+    }
+
+    if peer.roundTripTimeVariance > peer.highestRoundTripTimeVariance {
+        peer.highestRoundTripTimeVariance = peer.roundTripTimeVariance;
+    }
+
+    if peer.packetThrottleEpoch == 0
+        || ENET_TIME_DIFFERENCE(serviceTime, peer.packetThrottleEpoch)
+            >= peer.packetThrottleInterval
+    {
+        peer.lastRoundTripTime = peer.lowestRoundTripTime;
+        peer.lastRoundTripTimeVariance = ENET_MAX(peer.highestRoundTripTimeVariance, 1);
+        peer.lowestRoundTripTime = peer.roundTripTime;
+        peer.highestRoundTripTimeVariance = peer.roundTripTimeVariance;
+        peer.packetThrottleEpoch = serviceTime;
+    }
+
+    peer.lastReceiveTime = serviceTime.max(1);
+    peer.earliestTimeout = 0;
+
+    let commandNumber = enet_protocol_remove_sent_reliable_command(
+        host,
+        incomingPeerID,
+        acknowledge.receivedReliableSequenceNumber,
+        acknowledge.header.channelID,
+    );
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    match peer.state {
+        ENET_PEER_STATE_ACKNOWLEDGING_CONNECT => {
+            if commandNumber != ENET_PROTOCOL_COMMAND_VERIFY_CONNECT {
+                return -1;
+            }
+
+            enet_protocol_notify_connect(host, incomingPeerID, event);
+        }
+
+        ENET_PEER_STATE_DISCONNECTING => {
+            if commandNumber != ENET_PROTOCOL_COMMAND_DISCONNECT {
+                return -1;
+            }
+
+            enet_protocol_notify_disconnect(host, incomingPeerID, event);
+        }
+
+        ENET_PEER_STATE_DISCONNECT_LATER => {
+            let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+            if !enet_peer_has_outgoing_commands(peer) {
+                let eventData = peer.eventData;
+                enet_peer_disconnect(host, incomingPeerID, eventData);
+            }
+        }
+
+        _ => {}
+    }
+
+    0
+}
+
+pub fn enet_protocol_handle_connect(host: &mut ENetHost, command: &ENetProtocol) -> Option<u16> {
+    let connect = match command {
+        ENetProtocol::connect(x) => x,
+        _ => return None,
+    };
+
+    let mut channelCount = connect.channelCount as usize;
+
+    if channelCount < ENET_PROTOCOL_MINIMUM_CHANNEL_COUNT as usize
+        || channelCount > ENET_PROTOCOL_MAXIMUM_CHANNEL_COUNT as usize
+    {
+        return None;
+    }
+
+    let mut peer: Option<u16> = None;
+    let mut duplicatePeers = 0;
+
+    for currentPeer in host.peers.iter() {
+        if currentPeer.state == ENET_PEER_STATE_DISCONNECTED {
+            if peer.is_none() {
+                peer = Some(currentPeer.incomingPeerID);
+            }
+        } else if currentPeer.state != ENET_PEER_STATE_CONNECTING
+            && currentPeer.address.host == host.receivedAddress.host
+        {
+            if currentPeer.address.port == host.receivedAddress.port
+                && currentPeer.connectID == connect.connectID
+            {
+                return None;
+            }
+
+            duplicatePeers += 1;
+        }
+    }
+
+    let incomingPeerID = peer?;
+    if duplicatePeers >= host.duplicatePeers {
+        return None;
+    }
+
+    if channelCount > host.channelLimit {
+        channelCount = host.channelLimit;
+    }
+
+    let receivedAddress = host.receivedAddress;
+    let hostMtu = host.mtu;
+    let hostIncomingBandwidth = host.incomingBandwidth;
+    let hostOutgoingBandwidth = host.outgoingBandwidth;
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+
+    peer.channels = (0..channelCount).map(|_| ENetChannel::new()).collect();
+    peer.channelCount = channelCount;
+    peer.state = ENET_PEER_STATE_ACKNOWLEDGING_CONNECT;
+    peer.connectID = connect.connectID;
+    peer.address = receivedAddress;
+    peer.mtu = hostMtu;
+    peer.outgoingPeerID = connect.outgoingPeerID;
+    peer.incomingBandwidth = connect.incomingBandwidth;
+    peer.outgoingBandwidth = connect.outgoingBandwidth;
+    peer.packetThrottleInterval = connect.packetThrottleInterval;
+    peer.packetThrottleAcceleration = connect.packetThrottleAcceleration;
+    peer.packetThrottleDeceleration = connect.packetThrottleDeceleration;
+    peer.eventData = connect.data;
+
+    let sessionMask = (ENET_PROTOCOL_HEADER_SESSION_MASK as u32
+        >> ENET_PROTOCOL_HEADER_SESSION_SHIFT as u32) as u8;
+
+    let mut incomingSessionID = if connect.incomingSessionID == 0xFF {
+        peer.outgoingSessionID
+    } else {
+        connect.incomingSessionID
+    };
+    incomingSessionID = incomingSessionID.wrapping_add(1) & sessionMask;
+    if incomingSessionID == peer.outgoingSessionID {
+        incomingSessionID = incomingSessionID.wrapping_add(1) & sessionMask;
+    }
+    peer.outgoingSessionID = incomingSessionID;
+
+    let mut outgoingSessionID = if connect.outgoingSessionID == 0xFF {
+        peer.incomingSessionID
+    } else {
+        connect.outgoingSessionID
+    };
+    outgoingSessionID = outgoingSessionID.wrapping_add(1) & sessionMask;
+    if outgoingSessionID == peer.incomingSessionID {
+        outgoingSessionID = outgoingSessionID.wrapping_add(1) & sessionMask;
+    }
+    peer.incomingSessionID = outgoingSessionID;
+
+    let mut mtu = connect.mtu;
+
+    mtu = mtu.clamp(ENET_PROTOCOL_MINIMUM_MTU, ENET_PROTOCOL_MAXIMUM_MTU);
+
+    if mtu < peer.mtu {
+        peer.mtu = mtu;
+    }
+
+    if hostOutgoingBandwidth == 0 && peer.incomingBandwidth == 0 {
+        peer.windowSize = ENET_PROTOCOL_MAXIMUM_WINDOW_SIZE;
+    } else if hostOutgoingBandwidth == 0 || peer.incomingBandwidth == 0 {
+        peer.windowSize = (ENET_MAX(hostOutgoingBandwidth, peer.incomingBandwidth)
+            / ENET_PEER_WINDOW_SIZE_SCALE)
+            * ENET_PROTOCOL_MINIMUM_WINDOW_SIZE;
+    } else {
+        peer.windowSize = (ENET_MIN(hostOutgoingBandwidth, peer.incomingBandwidth)
+            / ENET_PEER_WINDOW_SIZE_SCALE)
+            * ENET_PROTOCOL_MINIMUM_WINDOW_SIZE;
+    }
+
+    peer.windowSize = peer.windowSize.clamp(
+        ENET_PROTOCOL_MINIMUM_WINDOW_SIZE,
+        ENET_PROTOCOL_MAXIMUM_WINDOW_SIZE,
+    );
+
+    let mut windowSize = if hostIncomingBandwidth == 0 {
+        ENET_PROTOCOL_MAXIMUM_WINDOW_SIZE
+    } else {
+        (hostIncomingBandwidth / ENET_PEER_WINDOW_SIZE_SCALE) * ENET_PROTOCOL_MINIMUM_WINDOW_SIZE
+    };
+
+    if windowSize > connect.windowSize {
+        windowSize = connect.windowSize;
+    }
+
+    windowSize = windowSize.clamp(
+        ENET_PROTOCOL_MINIMUM_WINDOW_SIZE,
+        ENET_PROTOCOL_MAXIMUM_WINDOW_SIZE,
+    );
+
+    let verifyCommand = ENetProtocol::verifyConnect(ENetProtocolVerifyConnect {
+        header: ENetProtocolCommandHeader {
+            command: (ENET_PROTOCOL_COMMAND_VERIFY_CONNECT as u32
+                | ENET_PROTOCOL_COMMAND_FLAG_ACKNOWLEDGE as u32) as u8,
+            channelID: 0xFF,
+            reliableSequenceNumber: 0,
+        },
+        outgoingPeerID: peer.incomingPeerID,
+        incomingSessionID,
+        outgoingSessionID,
+        mtu: peer.mtu,
+        windowSize,
+        channelCount: channelCount as u32,
+        incomingBandwidth: hostIncomingBandwidth,
+        outgoingBandwidth: hostOutgoingBandwidth,
+        packetThrottleInterval: peer.packetThrottleInterval,
+        packetThrottleAcceleration: peer.packetThrottleAcceleration,
+        packetThrottleDeceleration: peer.packetThrottleDeceleration,
+        connectID: peer.connectID,
+    });
+
+    enet_peer_queue_outgoing_command(host, incomingPeerID, &verifyCommand, None, 0, 0);
+
+    Some(incomingPeerID)
+}
+
+pub fn enet_protocol_handle_verify_connect(
+    host: &mut ENetHost,
+    event: Option<&mut ENetEvent>,
+    incomingPeerID: u16,
+    command: &ENetProtocol,
+) -> i32 {
+    let verifyConnect = match command {
+        ENetProtocol::verifyConnect(x) => x,
+        _ => return -1,
+    };
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if peer.state != ENET_PEER_STATE_CONNECTING {
+        return 0;
+    }
+
+    let channelCount = verifyConnect.channelCount as usize;
+
+    if channelCount < ENET_PROTOCOL_MINIMUM_CHANNEL_COUNT as usize
+        || channelCount > ENET_PROTOCOL_MAXIMUM_CHANNEL_COUNT as usize
+        || verifyConnect.packetThrottleInterval != peer.packetThrottleInterval
+        || verifyConnect.packetThrottleAcceleration != peer.packetThrottleAcceleration
+        || verifyConnect.packetThrottleDeceleration != peer.packetThrottleDeceleration
+        || verifyConnect.connectID != peer.connectID
+    {
+        peer.eventData = 0;
+
+        enet_protocol_dispatch_state(host, incomingPeerID, ENET_PEER_STATE_ZOMBIE);
+
+        return -1;
+    }
+
+    enet_protocol_remove_sent_reliable_command(host, incomingPeerID, 1, 0xFF);
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if channelCount < peer.channelCount {
+        peer.channelCount = channelCount;
+        peer.channels.truncate(channelCount);
+    }
+
+    peer.outgoingPeerID = verifyConnect.outgoingPeerID;
+    peer.incomingSessionID = verifyConnect.incomingSessionID;
+    peer.outgoingSessionID = verifyConnect.outgoingSessionID;
+
+    let mut mtu = verifyConnect.mtu;
+
+    mtu = mtu.clamp(ENET_PROTOCOL_MINIMUM_MTU, ENET_PROTOCOL_MAXIMUM_MTU);
+
+    if mtu < peer.mtu {
+        peer.mtu = mtu;
+    }
+
+    let windowSize = verifyConnect.windowSize.clamp(
+        ENET_PROTOCOL_MINIMUM_WINDOW_SIZE,
+        ENET_PROTOCOL_MAXIMUM_WINDOW_SIZE,
+    );
+
+    if windowSize < peer.windowSize {
+        peer.windowSize = windowSize;
+    }
+
+    peer.incomingBandwidth = verifyConnect.incomingBandwidth;
+    peer.outgoingBandwidth = verifyConnect.outgoingBandwidth;
+
+    enet_protocol_notify_connect(host, incomingPeerID, event);
+
+    0
+}
+
+pub fn enet_protocol_handle_disconnect(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    command: &ENetProtocol,
+) -> i32 {
+    let disconnect = match command {
+        ENetProtocol::disconnect(x) => x,
+        _ => return -1,
+    };
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if peer.state == ENET_PEER_STATE_DISCONNECTED
+        || peer.state == ENET_PEER_STATE_ZOMBIE
+        || peer.state == ENET_PEER_STATE_ACKNOWLEDGING_DISCONNECT
+    {
+        return 0;
+    }
+
+    enet_peer_reset_queues(host, incomingPeerID);
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if peer.state == ENET_PEER_STATE_CONNECTION_SUCCEEDED
+        || peer.state == ENET_PEER_STATE_DISCONNECTING
+        || peer.state == ENET_PEER_STATE_CONNECTING
+    {
+        enet_protocol_dispatch_state(host, incomingPeerID, ENET_PEER_STATE_ZOMBIE);
+    } else if peer.state != ENET_PEER_STATE_CONNECTED
+        && peer.state != ENET_PEER_STATE_DISCONNECT_LATER
+    {
+        if peer.state == ENET_PEER_STATE_CONNECTION_PENDING {
+            host.recalculateBandwidthLimits = 1;
+        }
+
+        enet_peer_reset(host, incomingPeerID);
+    } else if ((disconnect.header.command as u32) & (ENET_PROTOCOL_COMMAND_FLAG_ACKNOWLEDGE as u32))
+        != 0
+    {
+        enet_protocol_change_state(
+            host,
+            incomingPeerID,
+            ENET_PEER_STATE_ACKNOWLEDGING_DISCONNECT,
+        );
+    } else {
+        enet_protocol_dispatch_state(host, incomingPeerID, ENET_PEER_STATE_ZOMBIE);
+    }
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if peer.state != ENET_PEER_STATE_DISCONNECTED {
+        peer.eventData = disconnect.data;
+    }
+
+    0
+}
+
+pub fn enet_protocol_handle_bandwidth_limit(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    command: &ENetProtocol,
+) -> i32 {
+    let bandwidthLimit = match command {
+        ENetProtocol::bandwidthLimit(x) => x,
+        _ => return -1,
+    };
+
+    let hostOutgoingBandwidth = host.outgoingBandwidth;
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if peer.state != ENET_PEER_STATE_CONNECTED && peer.state != ENET_PEER_STATE_DISCONNECT_LATER {
+        return -1;
+    }
+
+    if peer.incomingBandwidth != 0 {
+        host.bandwidthLimitedPeers -= 1;
+    }
+
+    peer.incomingBandwidth = bandwidthLimit.incomingBandwidth;
+    peer.outgoingBandwidth = bandwidthLimit.outgoingBandwidth;
+
+    if peer.incomingBandwidth != 0 {
+        host.bandwidthLimitedPeers += 1;
+    }
+
+    if peer.incomingBandwidth == 0 && hostOutgoingBandwidth == 0 {
+        peer.windowSize = ENET_PROTOCOL_MAXIMUM_WINDOW_SIZE;
+    } else if peer.incomingBandwidth == 0 || hostOutgoingBandwidth == 0 {
+        peer.windowSize = (ENET_MAX(peer.incomingBandwidth, hostOutgoingBandwidth)
+            / ENET_PEER_WINDOW_SIZE_SCALE)
+            * ENET_PROTOCOL_MINIMUM_WINDOW_SIZE;
+    } else {
+        peer.windowSize = (ENET_MIN(peer.incomingBandwidth, hostOutgoingBandwidth)
+            / ENET_PEER_WINDOW_SIZE_SCALE)
+            * ENET_PROTOCOL_MINIMUM_WINDOW_SIZE;
+    }
+
+    peer.windowSize = peer.windowSize.clamp(
+        ENET_PROTOCOL_MINIMUM_WINDOW_SIZE,
+        ENET_PROTOCOL_MAXIMUM_WINDOW_SIZE,
+    );
+
+    0
+}
+
+pub fn enet_protocol_handle_throttle_configure(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    command: &ENetProtocol,
+) -> i32 {
+    let throttleConfigure = match command {
+        ENetProtocol::throttleConfigure(x) => x,
+        _ => return -1,
+    };
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if peer.state != ENET_PEER_STATE_CONNECTED && peer.state != ENET_PEER_STATE_DISCONNECT_LATER {
+        return -1;
+    }
+
+    peer.packetThrottleInterval = throttleConfigure.packetThrottleInterval;
+    peer.packetThrottleAcceleration = throttleConfigure.packetThrottleAcceleration;
+    peer.packetThrottleDeceleration = throttleConfigure.packetThrottleDeceleration;
+
+    0
+}
+
+pub fn enet_protocol_handle_ping(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    _: &ENetProtocol,
+) -> i32 {
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if peer.state != ENET_PEER_STATE_CONNECTED && peer.state != ENET_PEER_STATE_DISCONNECT_LATER {
+        return -1;
+    }
+
+    0
+}
+
+pub fn enet_protocol_handle_send_reliable(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    command: &ENetProtocol,
+    currentData: &mut usize,
+) -> i32 {
+    let sendReliable = match command {
+        ENetProtocol::sendReliable(x) => x,
+        _ => return -1,
+    };
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if sendReliable.header.channelID as usize >= peer.channelCount
+        || (peer.state != ENET_PEER_STATE_CONNECTED
+            && peer.state != ENET_PEER_STATE_DISCONNECT_LATER)
+    {
+        return -1;
+    }
+
+    let dataLength = sendReliable.dataLength as usize;
+    let data = *currentData;
+    *currentData += dataLength;
+    if dataLength > host.maximumPacketSize || *currentData > host.receivedDataLength {
+        return -1;
+    }
+
+    let data = host.packetData[host.receivedData][data..*currentData].to_vec();
+
+    enet_peer_queue_incoming_command(
+        host,
+        incomingPeerID,
+        command,
+        Some(data),
+        dataLength,
+        ENET_PACKET_FLAG_RELIABLE as u32,
+        0,
+    )
+}
+
+pub fn enet_protocol_handle_send_unreliable(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    command: &ENetProtocol,
+    currentData: &mut usize,
+) -> i32 {
+    let sendUnreliable = match command {
+        ENetProtocol::sendUnreliable(x) => x,
+        _ => return -1,
+    };
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if sendUnreliable.header.channelID as usize >= peer.channelCount
+        || (peer.state != ENET_PEER_STATE_CONNECTED
+            && peer.state != ENET_PEER_STATE_DISCONNECT_LATER)
+    {
+        return -1;
+    }
+
+    let dataLength = sendUnreliable.dataLength as usize;
+    let data = *currentData;
+    *currentData += dataLength;
+    if dataLength > host.maximumPacketSize || *currentData > host.receivedDataLength {
+        return -1;
+    }
+
+    let data = host.packetData[host.receivedData][data..*currentData].to_vec();
+
+    enet_peer_queue_incoming_command(host, incomingPeerID, command, Some(data), dataLength, 0, 0)
+}
+
+pub fn enet_protocol_handle_send_unsequenced(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    command: &ENetProtocol,
+    currentData: &mut usize,
+) -> i32 {
+    let sendUnsequenced = match command {
+        ENetProtocol::sendUnsequenced(x) => x,
+        _ => return -1,
+    };
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if sendUnsequenced.header.channelID as usize >= peer.channelCount
+        || (peer.state != ENET_PEER_STATE_CONNECTED
+            && peer.state != ENET_PEER_STATE_DISCONNECT_LATER)
+    {
+        return -1;
+    }
+
+    let dataLength = sendUnsequenced.dataLength as usize;
+    let data = *currentData;
+    *currentData += dataLength;
+    if dataLength > host.maximumPacketSize || *currentData > host.receivedDataLength {
+        return -1;
+    }
+
+    let mut unsequencedGroup = sendUnsequenced.unsequencedGroup as u32;
+    let index = unsequencedGroup % ENET_PEER_UNSEQUENCED_WINDOW_SIZE;
+
+    if unsequencedGroup < peer.incomingUnsequencedGroup as u32 {
+        unsequencedGroup += 0x10000;
+    }
+
+    if unsequencedGroup
+        >= peer.incomingUnsequencedGroup as u32
+            + ENET_PEER_FREE_UNSEQUENCED_WINDOWS * ENET_PEER_UNSEQUENCED_WINDOW_SIZE
+    {
+        return 0;
+    }
+
+    unsequencedGroup &= 0xFFFF;
+
+    if unsequencedGroup - index != peer.incomingUnsequencedGroup as u32 {
+        peer.incomingUnsequencedGroup = (unsequencedGroup - index) as u16;
+
+        peer.unsequencedWindow.fill(0);
+    } else if (peer.unsequencedWindow[(index / 32) as usize] & (1 << (index % 32))) != 0 {
+        return 0;
+    }
+
+    let data = host.packetData[host.receivedData][data..*currentData].to_vec();
+
+    if enet_peer_queue_incoming_command(
+        host,
+        incomingPeerID,
+        command,
+        Some(data),
+        dataLength,
+        ENET_PACKET_FLAG_UNSEQUENCED as u32,
+        0,
+    ) != 0
+    {
+        return -1;
+    }
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    peer.unsequencedWindow[(index / 32) as usize] |= 1 << (index % 32);
+
+    0
+}
+
+pub fn enet_protocol_handle_send_fragment(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    command: &ENetProtocol,
+    currentData: &mut usize,
+) -> i32 {
+    let sendFragment = match command {
+        ENetProtocol::sendFragment(x) => x,
+        _ => return -1,
+    };
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if sendFragment.header.channelID as usize >= peer.channelCount
+        || (peer.state != ENET_PEER_STATE_CONNECTED
+            && peer.state != ENET_PEER_STATE_DISCONNECT_LATER)
+    {
+        return -1;
+    }
+
+    let mut fragmentLength = sendFragment.dataLength as u32;
+    let data = *currentData;
+    *currentData += fragmentLength as usize;
+    if fragmentLength == 0
+        || fragmentLength as usize > host.maximumPacketSize
+        || *currentData > host.receivedDataLength
+    {
+        return -1;
+    }
+
+    let channelID = sendFragment.header.channelID;
+    let channel = &peer.channels[channelID as usize];
+    let startSequenceNumber = sendFragment.startSequenceNumber;
+    let mut startWindow = startSequenceNumber as u32 / ENET_PEER_RELIABLE_WINDOW_SIZE;
+    let currentWindow =
+        channel.incomingReliableSequenceNumber as u32 / ENET_PEER_RELIABLE_WINDOW_SIZE;
+
+    if startSequenceNumber < channel.incomingReliableSequenceNumber {
+        startWindow += ENET_PEER_RELIABLE_WINDOWS;
+    }
+
+    if startWindow < currentWindow
+        || startWindow >= currentWindow + ENET_PEER_FREE_RELIABLE_WINDOWS - 1
+    {
+        return 0;
+    }
+
+    let fragmentNumber = sendFragment.fragmentNumber;
+    let fragmentCount = sendFragment.fragmentCount;
+    let fragmentOffset = sendFragment.fragmentOffset;
+    let totalLength = sendFragment.totalLength;
+
+    if fragmentCount > ENET_PROTOCOL_MAXIMUM_FRAGMENT_COUNT
+        || fragmentNumber >= fragmentCount
+        || totalLength as usize > host.maximumPacketSize
+        || totalLength < fragmentCount
+        || fragmentOffset >= totalLength
+        || fragmentLength > totalLength - fragmentOffset
+    {
+        return -1;
+    }
+
+    let mut startCommand = enet_protocol_find_incoming_fragment(
+        &peer.channels[channelID as usize],
+        startSequenceNumber,
+    );
+
+    if let Some(currentCommand) = startCommand {
+        let incomingCommand =
+            &peer.channels[channelID as usize].incomingReliableCommands[currentCommand];
+        if ENetProtocolCommand::from(incomingCommand.command.command_header().command)
+            != ENET_PROTOCOL_COMMAND_SEND_FRAGMENT
+            || totalLength as usize != incomingCommand.packet.dataLength
+            || fragmentCount != incomingCommand.fragmentCount
+        {
+            return -1;
+        }
+    } else {
+        let mut hostCommand = *command;
+        hostCommand.command_header_mut().reliableSequenceNumber = startSequenceNumber;
+
+        if enet_peer_queue_incoming_command(
+            host,
+            incomingPeerID,
+            &hostCommand,
+            None,
+            totalLength as usize,
+            ENET_PACKET_FLAG_RELIABLE as u32,
+            fragmentCount,
+        ) != 0
+        {
+            return -1;
+        }
+
+        let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+        startCommand = enet_protocol_find_incoming_fragment(
+            &peer.channels[channelID as usize],
+            startSequenceNumber,
+        );
+    }
+
+    let startCommand = match startCommand {
+        Some(x) => x,
+        None => return -1,
+    };
+
+    let receivedData = &host.packetData[host.receivedData];
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    let incomingCommand =
+        &mut peer.channels[channelID as usize].incomingReliableCommands[startCommand];
+
+    if (incomingCommand.fragments[(fragmentNumber / 32) as usize] & (1 << (fragmentNumber % 32)))
+        == 0
+    {
+        incomingCommand.fragmentsRemaining -= 1;
+
+        incomingCommand.fragments[(fragmentNumber / 32) as usize] |= 1 << (fragmentNumber % 32);
+
+        if (fragmentOffset + fragmentLength) as usize > incomingCommand.packet.dataLength {
+            fragmentLength = incomingCommand.packet.dataLength as u32 - fragmentOffset;
+        }
+
+        if let Some(packetData) = &incomingCommand.packet.data {
+            packetData.borrow_mut()
+                [fragmentOffset as usize..(fragmentOffset + fragmentLength) as usize]
+                .copy_from_slice(&receivedData[data..data + fragmentLength as usize]);
+        }
+
+        if incomingCommand.fragmentsRemaining == 0 {
+            enet_peer_dispatch_incoming_reliable_commands(host, incomingPeerID, channelID);
+        }
+    }
+
+    0
+}
+
+pub fn enet_protocol_handle_send_unreliable_fragment(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    command: &ENetProtocol,
+    currentData: &mut usize,
+) -> i32 {
+    let sendFragment = match command {
+        ENetProtocol::sendFragment(x) => x,
+        _ => return -1,
+    };
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if sendFragment.header.channelID as usize >= peer.channelCount
+        || (peer.state != ENET_PEER_STATE_CONNECTED
+            && peer.state != ENET_PEER_STATE_DISCONNECT_LATER)
+    {
+        return -1;
+    }
+
+    let mut fragmentLength = sendFragment.dataLength as u32;
+    let data = *currentData;
+    *currentData += fragmentLength as usize;
+    if fragmentLength as usize > host.maximumPacketSize || *currentData > host.receivedDataLength {
+        return -1;
+    }
+
+    let channelID = sendFragment.header.channelID;
+    let channel = &peer.channels[channelID as usize];
+    let reliableSequenceNumber = sendFragment.header.reliableSequenceNumber;
+    let startSequenceNumber = sendFragment.startSequenceNumber;
+
+    let mut reliableWindow = reliableSequenceNumber as u32 / ENET_PEER_RELIABLE_WINDOW_SIZE;
+    let currentWindow =
+        channel.incomingReliableSequenceNumber as u32 / ENET_PEER_RELIABLE_WINDOW_SIZE;
+
+    if reliableSequenceNumber < channel.incomingReliableSequenceNumber {
+        reliableWindow += ENET_PEER_RELIABLE_WINDOWS;
+    }
+
+    if reliableWindow < currentWindow
+        || reliableWindow >= currentWindow + ENET_PEER_FREE_RELIABLE_WINDOWS - 1
+    {
+        return 0;
+    }
+
+    if reliableSequenceNumber == channel.incomingReliableSequenceNumber
+        && startSequenceNumber <= channel.incomingUnreliableSequenceNumber
+    {
+        return 0;
+    }
+
+    let fragmentNumber = sendFragment.fragmentNumber;
+    let fragmentCount = sendFragment.fragmentCount;
+    let fragmentOffset = sendFragment.fragmentOffset;
+    let totalLength = sendFragment.totalLength;
+
+    if fragmentCount > ENET_PROTOCOL_MAXIMUM_FRAGMENT_COUNT
+        || fragmentNumber >= fragmentCount
+        || totalLength as usize > host.maximumPacketSize
+        || totalLength < fragmentCount
+        || fragmentOffset >= totalLength
+        || fragmentLength > totalLength - fragmentOffset
+    {
+        return -1;
+    }
+
+    let mut startCommand = enet_protocol_find_incoming_unreliable_fragment(
+        channel,
+        reliableSequenceNumber,
+        startSequenceNumber,
+    );
+
+    if let Some(currentCommand) = startCommand {
+        let incomingCommand = &channel.incomingUnreliableCommands[currentCommand];
+        if ENetProtocolCommand::from(incomingCommand.command.command_header().command)
+            != ENET_PROTOCOL_COMMAND_SEND_UNRELIABLE_FRAGMENT
+            || totalLength as usize != incomingCommand.packet.dataLength
+            || fragmentCount != incomingCommand.fragmentCount
+        {
+            return -1;
+        }
+    } else {
+        if enet_peer_queue_incoming_command(
+            host,
+            incomingPeerID,
+            command,
+            None,
+            totalLength as usize,
+            ENET_PACKET_FLAG_UNRELIABLE_FRAGMENT as u32,
+            fragmentCount,
+        ) != 0
+        {
+            return -1;
+        }
+
+        let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+        startCommand = enet_protocol_find_incoming_unreliable_fragment(
+            &peer.channels[channelID as usize],
+            reliableSequenceNumber,
+            startSequenceNumber,
+        );
+    }
+
+    let startCommand = match startCommand {
+        Some(x) => x,
+        None => return -1,
+    };
+
+    let receivedData = &host.packetData[host.receivedData];
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    let incomingCommand =
+        &mut peer.channels[channelID as usize].incomingUnreliableCommands[startCommand];
+
+    if (incomingCommand.fragments[(fragmentNumber / 32) as usize] & (1 << (fragmentNumber % 32)))
+        == 0
+    {
+        incomingCommand.fragmentsRemaining -= 1;
+
+        incomingCommand.fragments[(fragmentNumber / 32) as usize] |= 1 << (fragmentNumber % 32);
+
+        if (fragmentOffset + fragmentLength) as usize > incomingCommand.packet.dataLength {
+            fragmentLength = incomingCommand.packet.dataLength as u32 - fragmentOffset;
+        }
+
+        if let Some(packetData) = &incomingCommand.packet.data {
+            packetData.borrow_mut()
+                [fragmentOffset as usize..(fragmentOffset + fragmentLength) as usize]
+                .copy_from_slice(&receivedData[data..data + fragmentLength as usize]);
+        }
+
+        if incomingCommand.fragmentsRemaining == 0 {
+            enet_peer_dispatch_incoming_unreliable_commands(host, incomingPeerID, channelID, None);
+        }
+    }
+
+    0
+}
+
+pub fn enet_protocol_find_incoming_fragment(
+    channel: &ENetChannel,
+    startSequenceNumber: u16,
+) -> Option<usize> {
+    let mut currentCommand = channel.incomingReliableCommands.len();
+    while currentCommand > 0 {
+        currentCommand -= 1;
+
+        let incomingCommand = &channel.incomingReliableCommands[currentCommand];
+
+        if startSequenceNumber >= channel.incomingReliableSequenceNumber {
+            if incomingCommand.reliableSequenceNumber < channel.incomingReliableSequenceNumber {
+                continue;
+            }
+        } else if incomingCommand.reliableSequenceNumber >= channel.incomingReliableSequenceNumber {
+            break;
+        }
+
+        if incomingCommand.reliableSequenceNumber <= startSequenceNumber {
+            if incomingCommand.reliableSequenceNumber < startSequenceNumber {
+                break;
+            }
+
+            return Some(currentCommand);
+        }
+    }
+
+    None
+}
+
+pub fn enet_protocol_find_incoming_unreliable_fragment(
+    channel: &ENetChannel,
+    reliableSequenceNumber: u16,
+    startSequenceNumber: u16,
+) -> Option<usize> {
+    let mut currentCommand = channel.incomingUnreliableCommands.len();
+    while currentCommand > 0 {
+        currentCommand -= 1;
+
+        let incomingCommand = &channel.incomingUnreliableCommands[currentCommand];
+
+        if reliableSequenceNumber >= channel.incomingReliableSequenceNumber {
+            if incomingCommand.reliableSequenceNumber < channel.incomingReliableSequenceNumber {
+                continue;
+            }
+        } else if incomingCommand.reliableSequenceNumber >= channel.incomingReliableSequenceNumber {
+            break;
+        }
+
+        if incomingCommand.reliableSequenceNumber < reliableSequenceNumber {
+            break;
+        }
+
+        if incomingCommand.reliableSequenceNumber > reliableSequenceNumber {
+            continue;
+        }
+
+        if incomingCommand.unreliableSequenceNumber <= startSequenceNumber {
+            if incomingCommand.unreliableSequenceNumber < startSequenceNumber {
+                break;
+            }
+
+            return Some(currentCommand);
+        }
+    }
+
+    None
+}
+
+#[allow(clippy::nonminimal_bool)]
+pub fn enet_protocol_handle_incoming_commands(
+    host: &mut ENetHost,
+    mut event: Option<&mut ENetEvent>,
+) -> i32 {
+    let (header, mut headerSize) = match enet_protocol_decode_header(
+        &host.packetData[host.receivedData][..host.receivedDataLength],
+    ) {
+        Ok(x) => x,
+        Err(_) => return 0,
+    };
+
+    let mut peerID = header.peerID;
+    let sessionID = ((peerID as u32 & ENET_PROTOCOL_HEADER_SESSION_MASK as u32)
+        >> ENET_PROTOCOL_HEADER_SESSION_SHIFT as u32) as u8;
+    let flags = peerID as u32 & ENET_PROTOCOL_HEADER_FLAG_MASK as u32;
+    peerID &= !((ENET_PROTOCOL_HEADER_FLAG_MASK as u32 | ENET_PROTOCOL_HEADER_SESSION_MASK as u32)
+        as u16);
+
+    if host.checksum.is_some() {
+        headerSize += 4;
+    }
+
+    if host.receivedDataLength < headerSize {
+        return 0;
+    }
+
+    let mut peer: Option<u16> = None;
+
+    if peerID as u32 == ENET_PROTOCOL_MAXIMUM_PEER_ID {
+    } else if peerID as usize >= host.peers.len() {
+        return 0;
+    } else {
+        let receivedAddress = host.receivedAddress;
+        let currentPeer = enet_host_get_mut_peer!(host, peerID);
+
+        if currentPeer.state == ENET_PEER_STATE_DISCONNECTED
+            || currentPeer.state == ENET_PEER_STATE_ZOMBIE
+            || ((receivedAddress.host != currentPeer.address.host
+                || receivedAddress.port != currentPeer.address.port)
+                && currentPeer.address.host != ENET_HOST_BROADCAST)
+            || ((currentPeer.outgoingPeerID as u32) < ENET_PROTOCOL_MAXIMUM_PEER_ID
+                && sessionID != currentPeer.incomingSessionID)
+        {
+            return 0;
+        }
+
+        peer = Some(peerID);
+    }
+
+    if (flags & ENET_PROTOCOL_HEADER_FLAG_COMPRESSED as u32) != 0 {
+        let decompress = match host.compressor.decompress {
+            Some(x) => x,
+            None => return 0,
+        };
+
+        if host.compressor.context.is_none() {
+            return 0;
+        }
+
+        let [receivedData, packetData] = &mut host.packetData;
+        let originalSize = decompress(
+            host.compressor.context.as_mut(),
+            &receivedData[headerSize..host.receivedDataLength],
+            host.receivedDataLength - headerSize,
+            &mut packetData[headerSize..],
+            ENET_PROTOCOL_MAXIMUM_MTU as usize - headerSize,
+        );
+
+        if originalSize == 0 || originalSize > ENET_PROTOCOL_MAXIMUM_MTU as usize - headerSize {
+            return 0;
+        }
+
+        packetData[..headerSize].copy_from_slice(&receivedData[..headerSize]);
+        host.receivedData = 1;
+        host.receivedDataLength = headerSize + originalSize;
+    }
+
+    if let Some(checksum) = host.checksum {
+        let newChecksum = match peer {
+            Some(peerID) => enet_host_get_mut_peer!(host, peerID).connectID,
+            None => 0,
+        };
+
+        let receivedData = &mut host.packetData[host.receivedData];
+        let desiredChecksum =
+            u32::from_ne_bytes(receivedData[headerSize - 4..headerSize].try_into().unwrap());
+
+        receivedData[headerSize - 4..headerSize].copy_from_slice(&newChecksum.to_be_bytes());
+
+        let data: [&[u8]; 1] = [&receivedData[..]];
+        let buffer = ENetBuffer {
+            dataID: 0,
+            dataLength: host.receivedDataLength,
+        };
+
+        if checksum(&data, &[buffer], 1) != desiredChecksum {
+            return 0;
+        }
+    }
+
+    if let Some(peerID) = peer {
+        let receivedAddress = host.receivedAddress;
+        let receivedDataLength = host.receivedDataLength;
+        let currentPeer = enet_host_get_mut_peer!(host, peerID);
+        currentPeer.address = receivedAddress;
+        currentPeer.incomingDataTotal = currentPeer
+            .incomingDataTotal
+            .wrapping_add(receivedDataLength as u32);
+    }
+
+    let mut currentData = headerSize;
+
+    while currentData < host.receivedDataLength {
+        let command = match enet_protocol_decode_command(
+            &host.packetData[host.receivedData][currentData..host.receivedDataLength],
+        ) {
+            Ok(x) => x,
+            Err(_) => break,
+        };
+
+        let commandNumber =
+            (command.command_header().command as u32 & ENET_PROTOCOL_COMMAND_MASK as u32) as u8;
+        currentData += enet_protocol_command_size(commandNumber);
+
+        if peer.is_none() && commandNumber != ENET_PROTOCOL_COMMAND_CONNECT as u8 {
+            break;
+        }
+
+        let result = match (ENetProtocolCommand::from(commandNumber), peer) {
+            (ENET_PROTOCOL_COMMAND_CONNECT, None) => {
+                match enet_protocol_handle_connect(host, &command) {
+                    Some(peerID) => {
+                        peer = Some(peerID);
+                        0
+                    }
+
+                    None => -1,
+                }
+            }
+
+            (ENET_PROTOCOL_COMMAND_ACKNOWLEDGE, Some(peerID)) => {
+                enet_protocol_handle_acknowledge(host, event.as_deref_mut(), peerID, &command)
+            }
+
+            (ENET_PROTOCOL_COMMAND_VERIFY_CONNECT, Some(peerID)) => {
+                enet_protocol_handle_verify_connect(host, event.as_deref_mut(), peerID, &command)
+            }
+
+            (ENET_PROTOCOL_COMMAND_DISCONNECT, Some(peerID)) => {
+                enet_protocol_handle_disconnect(host, peerID, &command)
+            }
+
+            (ENET_PROTOCOL_COMMAND_PING, Some(peerID)) => {
+                enet_protocol_handle_ping(host, peerID, &command)
+            }
+
+            (ENET_PROTOCOL_COMMAND_BANDWIDTH_LIMIT, Some(peerID)) => {
+                enet_protocol_handle_bandwidth_limit(host, peerID, &command)
+            }
+
+            (ENET_PROTOCOL_COMMAND_THROTTLE_CONFIGURE, Some(peerID)) => {
+                enet_protocol_handle_throttle_configure(host, peerID, &command)
+            }
+
+            (ENET_PROTOCOL_COMMAND_SEND_RELIABLE, Some(peerID)) => {
+                enet_protocol_handle_send_reliable(host, peerID, &command, &mut currentData)
+            }
+
+            (ENET_PROTOCOL_COMMAND_SEND_UNRELIABLE, Some(peerID)) => {
+                enet_protocol_handle_send_unreliable(host, peerID, &command, &mut currentData)
+            }
+
+            (ENET_PROTOCOL_COMMAND_SEND_UNSEQUENCED, Some(peerID)) => {
+                enet_protocol_handle_send_unsequenced(host, peerID, &command, &mut currentData)
+            }
+
+            (ENET_PROTOCOL_COMMAND_SEND_FRAGMENT, Some(peerID)) => {
+                enet_protocol_handle_send_fragment(host, peerID, &command, &mut currentData)
+            }
+
+            (ENET_PROTOCOL_COMMAND_SEND_UNRELIABLE_FRAGMENT, Some(peerID)) => {
+                enet_protocol_handle_send_unreliable_fragment(
+                    host,
+                    peerID,
+                    &command,
+                    &mut currentData,
+                )
+            }
+
+            _ => -1,
+        };
+
+        if result != 0 {
+            break;
+        }
+
+        let peerID = match peer {
+            Some(x) => x,
+            None => break,
+        };
+
+        if ((command.command_header().command as u32)
+            & (ENET_PROTOCOL_COMMAND_FLAG_ACKNOWLEDGE as u32))
+            != 0
+        {
+            if !((flags & ENET_PROTOCOL_HEADER_FLAG_SENT_TIME as u32) != 0) {
+                break;
+            }
+
+            let sentTime = header.sentTime;
+
+            let currentPeer = enet_host_get_mut_peer!(host, peerID);
+            match currentPeer.state {
+                ENET_PEER_STATE_DISCONNECTING
+                | ENET_PEER_STATE_ACKNOWLEDGING_CONNECT
+                | ENET_PEER_STATE_DISCONNECTED
+                | ENET_PEER_STATE_ZOMBIE => {}
+
+                ENET_PEER_STATE_ACKNOWLEDGING_DISCONNECT => {
+                    if ENetProtocolCommand::from(command.command_header().command)
+                        == ENET_PROTOCOL_COMMAND_DISCONNECT
+                    {
+                        enet_peer_queue_acknowledgement(host, peerID, &command, sentTime);
+                    }
+                }
+
+                _ => {
+                    enet_peer_queue_acknowledgement(host, peerID, &command, sentTime);
+                }
+            }
+        }
+    }
+
+    if let Some(event) = event {
+        if event.event_type() != ENetEventType::ENET_EVENT_TYPE_NONE {
+            return 1;
+        }
+    }
+
+    0
+}
+
+pub fn enet_protocol_receive_incoming_commands(
+    host: &mut ENetHost,
+    mut event: Option<&mut ENetEvent>,
+) -> Result<i32, ENetError> {
+    for _ in 0..256 {
+        let mut buffers = [ENetBuffer {
+            dataID: 0,
+            dataLength: ENET_PROTOCOL_MAXIMUM_MTU as usize,
+        }];
+
+        let mut data: [&mut [u8]; 1] = [&mut host.packetData[0]];
+
+        let receivedLength = crate::c_win32::enet_socket_receive(
+            &*host.socket,
+            &mut host.receivedAddress,
+            &mut data,
+            &mut buffers,
+            1,
+        )?;
+
+        if receivedLength == 0 {
+            return Ok(0);
+        }
+
+        host.receivedData = 0;
+        host.receivedDataLength = receivedLength;
+
+        host.totalReceivedData = host.totalReceivedData.wrapping_add(receivedLength as u32);
+        host.totalReceivedPackets = host.totalReceivedPackets.wrapping_add(1);
+
+        if let Some(intercept) = host.intercept {
+            let mut interceptEvent = ENetEvent::default();
+            let result = match event.as_deref_mut() {
+                Some(event) => intercept(host, event)?,
+                None => intercept(host, &mut interceptEvent)?,
+            };
+
+            if result == 1 {
+                if let Some(event) = event.as_deref() {
+                    if event.event_type() != ENetEventType::ENET_EVENT_TYPE_NONE {
+                        return Ok(1);
+                    }
+                }
+
+                continue;
+            }
+        }
+
+        if enet_protocol_handle_incoming_commands(host, event.as_deref_mut()) == 1 {
+            return Ok(1);
+        }
+    }
+
+    Ok(0)
+}
+
+pub fn enet_protocol_send_acknowledgements(host: &mut ENetHost, incomingPeerID: u16) {
+    loop {
+        let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+        let acknowledgement = match peer.acknowledgements.front() {
+            Some(x) => x,
+            None => break,
+        };
+
+        if host.commandCount >= host.commands.len()
+            || host.bufferCount >= host.buffers.len()
+            || (peer.mtu as usize).wrapping_sub(host.packetSize)
+                < commandSizes[ENET_PROTOCOL_COMMAND_ACKNOWLEDGE as usize]
+        {
+            let mut flags = peer.flags as u32;
+            flags |= ENET_PEER_FLAG_CONTINUE_SENDING as u32;
+            peer.flags = flags as u16;
+
+            break;
+        }
+
+        let header = *acknowledgement.command.command_header();
+        let sentTime = acknowledgement.sentTime;
+
+        host.buffers[host.bufferCount] = ENetBuffer {
+            dataID: host.bufferCount,
+            dataLength: commandSizes[ENET_PROTOCOL_COMMAND_ACKNOWLEDGE as usize],
+        };
+
+        host.packetSize += host.buffers[host.bufferCount].dataLength;
+
+        host.commands[host.commandCount] = ENetProtocol::acknowledge(ENetProtocolAcknowledge {
+            header: ENetProtocolCommandHeader {
+                command: ENET_PROTOCOL_COMMAND_ACKNOWLEDGE as u8,
+                channelID: header.channelID,
+                reliableSequenceNumber: header.reliableSequenceNumber,
+            },
+            receivedReliableSequenceNumber: header.reliableSequenceNumber,
+            receivedSentTime: sentTime as u16,
+        });
+
+        peer.acknowledgements.pop_front();
+
+        host.commandCount += 1;
+        host.bufferCount += 1;
+
+        if ENetProtocolCommand::from(header.command) == ENET_PROTOCOL_COMMAND_DISCONNECT {
+            enet_protocol_dispatch_state(host, incomingPeerID, ENET_PEER_STATE_ZOMBIE);
+        }
+    }
+}
+
+pub fn enet_protocol_check_timeouts(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    event: Option<&mut ENetEvent>,
+) -> i32 {
+    let serviceTime = host.serviceTime;
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+
+    let mut currentCommand = 0;
+    let mut insertPosition = 0;
+    let mut insertSendReliablePosition = 0;
+
+    while currentCommand < peer.sentReliableCommands.len() {
+        let outgoingCommand = &peer.sentReliableCommands[currentCommand];
+
+        if ENET_TIME_DIFFERENCE(serviceTime, outgoingCommand.sentTime)
+            < outgoingCommand.roundTripTimeout
+        {
+            currentCommand += 1;
+            continue;
+        }
+
+        if peer.earliestTimeout == 0
+            || ENET_TIME_LESS(outgoingCommand.sentTime, peer.earliestTimeout)
+        {
+            peer.earliestTimeout = outgoingCommand.sentTime;
+        }
+
+        if peer.earliestTimeout != 0
+            && (ENET_TIME_DIFFERENCE(serviceTime, peer.earliestTimeout) >= peer.timeoutMaximum
+                || (1u32
+                    .checked_shl(outgoingCommand.sendAttempts as u32 - 1)
+                    .unwrap_or(u32::MAX)
+                    >= peer.timeoutLimit
+                    && ENET_TIME_DIFFERENCE(serviceTime, peer.earliestTimeout)
+                        >= peer.timeoutMinimum))
+        {
+            enet_protocol_notify_disconnect(host, incomingPeerID, event);
+
+            return 1;
+        }
+
+        peer.packetsLost += 1;
+
+        let mut outgoingCommand = peer.sentReliableCommands.remove(currentCommand).unwrap();
+
+        outgoingCommand.roundTripTimeout = outgoingCommand.roundTripTimeout.saturating_mul(2);
+
+        if outgoingCommand.packet.is_some() {
+            peer.reliableDataInTransit -= outgoingCommand.fragmentLength as u32;
+
+            peer.outgoingSendReliableCommands
+                .insert(insertSendReliablePosition, outgoingCommand);
+            insertSendReliablePosition += 1;
+        } else {
+            peer.outgoingCommands
+                .insert(insertPosition, outgoingCommand);
+            insertPosition += 1;
+        }
+
+        if currentCommand == 0 {
+            if let Some(outgoingCommand) = peer.sentReliableCommands.front() {
+                peer.nextTimeout = outgoingCommand
+                    .sentTime
+                    .wrapping_add(outgoingCommand.roundTripTimeout);
+            }
+        }
+    }
+
+    0
+}
+
+pub fn enet_protocol_reliable_window_stalled(
+    peer: &ENetPeer,
+    outgoingCommand: &ENetOutgoingCommand,
+) -> bool {
+    let channel = &peer.channels[outgoingCommand.command.command_header().channelID as usize];
+    let reliableWindow =
+        outgoingCommand.reliableSequenceNumber as u32 / ENET_PEER_RELIABLE_WINDOW_SIZE;
+    let previousWindow =
+        (reliableWindow + ENET_PEER_RELIABLE_WINDOWS - 1) % ENET_PEER_RELIABLE_WINDOWS;
+    let freeWindows = (1u32 << (ENET_PEER_FREE_RELIABLE_WINDOWS + 2)) - 1;
+
+    outgoingCommand.sendAttempts < 1
+        && (outgoingCommand.reliableSequenceNumber as u32)
+            .is_multiple_of(ENET_PEER_RELIABLE_WINDOW_SIZE)
+        && (channel.reliableWindows[previousWindow as usize] as u32
+            >= ENET_PEER_RELIABLE_WINDOW_SIZE
+            || channel.usedReliableWindows as u32
+                & ((freeWindows << reliableWindow)
+                    | (freeWindows >> (ENET_PEER_RELIABLE_WINDOWS - reliableWindow)))
+                != 0)
+}
+
+pub fn enet_protocol_reliable_data_window_full(
+    peer: &ENetPeer,
+    outgoingCommand: &ENetOutgoingCommand,
+) -> bool {
+    let windowSize = (peer.packetThrottle * peer.windowSize) / ENET_PEER_PACKET_THROTTLE_SCALE;
+
+    peer.reliableDataInTransit + outgoingCommand.fragmentLength as u32
+        > ENET_MAX(windowSize, peer.mtu)
+}
+
+pub fn enet_protocol_has_sendable_commands(peer: &ENetPeer) -> bool {
+    let mut currentCommand = 0;
+    let mut currentSendReliableCommand = 0;
+    let mut windowWrap = false;
+
+    loop {
+        let useSendReliableCommand = if currentCommand < peer.outgoingCommands.len() {
+            currentSendReliableCommand < peer.outgoingSendReliableCommands.len()
+                && ENET_TIME_LESS(
+                    peer.outgoingSendReliableCommands[currentSendReliableCommand].queueTime,
+                    peer.outgoingCommands[currentCommand].queueTime,
+                )
+        } else if currentSendReliableCommand < peer.outgoingSendReliableCommands.len() {
+            true
+        } else {
+            return false;
+        };
+
+        let outgoingCommand = if useSendReliableCommand {
+            currentSendReliableCommand += 1;
+            &peer.outgoingSendReliableCommands[currentSendReliableCommand - 1]
+        } else {
+            currentCommand += 1;
+            &peer.outgoingCommands[currentCommand - 1]
+        };
+
+        let header = outgoingCommand.command.command_header();
+        if (header.command as u32) & (ENET_PROTOCOL_COMMAND_FLAG_ACKNOWLEDGE as u32) == 0 {
+            return true;
+        }
+
+        if (header.channelID as usize) < peer.channelCount {
+            if windowWrap {
+                continue;
+            }
+
+            if enet_protocol_reliable_window_stalled(peer, outgoingCommand) {
+                windowWrap = true;
+                currentSendReliableCommand = peer.outgoingSendReliableCommands.len();
+                continue;
+            }
+        }
+
+        if outgoingCommand.packet.is_some()
+            && enet_protocol_reliable_data_window_full(peer, outgoingCommand)
+        {
+            currentSendReliableCommand = peer.outgoingSendReliableCommands.len();
+            continue;
+        }
+
+        return true;
+    }
+}
+
+pub fn enet_protocol_check_outgoing_commands(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+    sentUnreliableCommands: &mut VecDeque<ENetOutgoingCommand>,
+) -> i32 {
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    let mut currentCommand = 0;
+    let mut currentSendReliableCommand = 0;
+    let mut canPing = 1;
+    let mut windowWrap = false;
+
+    loop {
+        let useSendReliableCommand = if currentCommand < peer.outgoingCommands.len() {
+            currentSendReliableCommand < peer.outgoingSendReliableCommands.len()
+                && ENET_TIME_LESS(
+                    peer.outgoingSendReliableCommands[currentSendReliableCommand].queueTime,
+                    peer.outgoingCommands[currentCommand].queueTime,
+                )
+        } else if currentSendReliableCommand < peer.outgoingSendReliableCommands.len() {
+            true
+        } else {
+            break;
+        };
+
+        let outgoingCommand = if useSendReliableCommand {
+            &peer.outgoingSendReliableCommands[currentSendReliableCommand]
+        } else {
+            &peer.outgoingCommands[currentCommand]
+        };
+
+        let header = outgoingCommand.command.command_header();
+        let command = header.command;
+        let isReliable = ((command as u32) & (ENET_PROTOCOL_COMMAND_FLAG_ACKNOWLEDGE as u32)) != 0;
+        let commandSize = enet_protocol_command_size(command);
+        let fragmentLength = outgoingCommand.fragmentLength as usize;
+        let hasPacket = outgoingCommand.packet.is_some();
+        let fragmentOffset = outgoingCommand.fragmentOffset;
+        let reliableSequenceNumber = outgoingCommand.reliableSequenceNumber;
+        let unreliableSequenceNumber = outgoingCommand.unreliableSequenceNumber;
+
+        let channelID = header.channelID as usize;
+        let hasChannel = channelID < peer.channelCount;
+        let reliableWindow = reliableSequenceNumber as u32 / ENET_PEER_RELIABLE_WINDOW_SIZE;
+
+        if isReliable {
+            if hasChannel {
+                if windowWrap {
+                    if useSendReliableCommand {
+                        currentSendReliableCommand += 1;
+                    } else {
+                        currentCommand += 1;
+                    }
+
+                    continue;
+                } else if enet_protocol_reliable_window_stalled(peer, outgoingCommand) {
+                    windowWrap = true;
+                    currentSendReliableCommand = peer.outgoingSendReliableCommands.len();
+
+                    if !useSendReliableCommand {
+                        currentCommand += 1;
+                    }
+
+                    continue;
+                }
+            }
+
+            if hasPacket && enet_protocol_reliable_data_window_full(peer, outgoingCommand) {
+                currentSendReliableCommand = peer.outgoingSendReliableCommands.len();
+
+                if !useSendReliableCommand {
+                    currentCommand += 1;
+                }
+
+                continue;
+            }
+
+            canPing = 0;
+        }
+
+        if host.commandCount >= host.commands.len()
+            || host.bufferCount + 1 >= host.buffers.len()
+            || (peer.mtu as usize).wrapping_sub(host.packetSize) < commandSize
+            || (hasPacket
+                && ((peer.mtu as usize).wrapping_sub(host.packetSize) as u16)
+                    < ((commandSize + fragmentLength) as u16))
+        {
+            let mut flags = peer.flags as u32;
+            flags |= ENET_PEER_FLAG_CONTINUE_SENDING as u32;
+            peer.flags = flags as u16;
+
+            break;
+        }
+
+        if !isReliable && hasPacket && fragmentOffset == 0 {
+            peer.packetThrottleCounter += ENET_PEER_PACKET_THROTTLE_COUNTER;
+            peer.packetThrottleCounter %= ENET_PEER_PACKET_THROTTLE_SCALE;
+
+            if peer.packetThrottleCounter > peer.packetThrottle {
+                loop {
+                    let outgoingCommand = peer.outgoingCommands.remove(currentCommand).unwrap();
+                    if let Some(packet) = outgoingCommand.packet {
+                        enet_packet_release(packet, 0);
+                    }
+
+                    match peer.outgoingCommands.get(currentCommand) {
+                        Some(outgoingCommand)
+                            if outgoingCommand.reliableSequenceNumber == reliableSequenceNumber
+                                && outgoingCommand.unreliableSequenceNumber
+                                    == unreliableSequenceNumber => {}
+                        _ => break,
+                    }
+                }
+
+                continue;
+            }
+        }
+
+        let mut outgoingCommand = if useSendReliableCommand {
+            peer.outgoingSendReliableCommands
+                .remove(currentSendReliableCommand)
+                .unwrap()
+        } else {
+            peer.outgoingCommands.remove(currentCommand).unwrap()
+        };
+
+        if isReliable {
+            if hasChannel && outgoingCommand.sendAttempts < 1 {
+                let channel = &mut peer.channels[channelID];
+                channel.usedReliableWindows |= 1 << reliableWindow;
+                channel.reliableWindows[reliableWindow as usize] += 1;
+            }
+
+            outgoingCommand.sendAttempts += 1;
+
+            if outgoingCommand.roundTripTimeout == 0 {
+                outgoingCommand.roundTripTimeout =
+                    peer.roundTripTime + 4 * peer.roundTripTimeVariance;
+            }
+
+            if peer.sentReliableCommands.is_empty() {
+                peer.nextTimeout = host
+                    .serviceTime
+                    .wrapping_add(outgoingCommand.roundTripTimeout);
+            }
+
+            outgoingCommand.sentTime = host.serviceTime;
+
+            host.headerFlags |= ENET_PROTOCOL_HEADER_FLAG_SENT_TIME as u16;
+
+            peer.reliableDataInTransit += outgoingCommand.fragmentLength as u32;
+        }
+
+        host.buffers[host.bufferCount] = ENetBuffer {
+            dataID: host.bufferCount,
+            dataLength: commandSize,
+        };
+
+        host.packetSize += commandSize;
+
+        host.commands[host.commandCount] = outgoingCommand.command;
+
+        if let Some(packet) = &outgoingCommand.packet {
+            host.bufferCount += 1;
+
+            host.buffers[host.bufferCount] = ENetBuffer {
+                dataID: host.bufferCount,
+                dataLength: fragmentLength,
+            };
+
+            host.bufferPackets[host.bufferCount] = packet.borrow().data.clone();
+
+            host.packetSize += fragmentLength;
+        }
+
+        if isReliable {
+            peer.sentReliableCommands.push_back(outgoingCommand);
+        } else if hasPacket {
+            sentUnreliableCommands.push_back(outgoingCommand);
+        }
+
+        peer.packetsSent += 1;
+
+        host.commandCount += 1;
+        host.bufferCount += 1;
+    }
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+    if peer.state == ENET_PEER_STATE_DISCONNECT_LATER
+        && !enet_peer_has_outgoing_commands(peer)
+        && sentUnreliableCommands.is_empty()
+    {
+        let eventData = peer.eventData;
+        enet_peer_disconnect(host, incomingPeerID, eventData);
+    }
+
+    canPing
+}
+
+#[allow(clippy::nonminimal_bool)]
+pub fn enet_protocol_send_outgoing_commands(
+    host: &mut ENetHost,
+    mut event: Option<&mut ENetEvent>,
+    checkForTimeouts: i32,
+) -> Result<i32, ENetError> {
+    let mut sentUnreliableCommands: VecDeque<ENetOutgoingCommand> = VecDeque::new();
+
+    let mut sendPass = 0;
+    let mut continueSending = 0;
+
+    while sendPass <= continueSending {
+        for i in 0..host.peers.len() {
+            let incomingPeerID = i as u16;
+            let currentPeer = enet_host_get_mut_peer!(host, incomingPeerID);
+
+            if currentPeer.state == ENET_PEER_STATE_DISCONNECTED
+                || currentPeer.state == ENET_PEER_STATE_ZOMBIE
+                || (sendPass > 0
+                    && !(((currentPeer.flags as u32) & (ENET_PEER_FLAG_CONTINUE_SENDING as u32))
+                        != 0))
+            {
+                continue;
+            }
+
+            let mut flags = currentPeer.flags as u32;
+            flags &= !(ENET_PEER_FLAG_CONTINUE_SENDING as u32);
+            currentPeer.flags = flags as u16;
+
+            host.headerFlags = 0;
+            host.commandCount = 0;
+            host.bufferCount = 1;
+            host.packetSize = ENET_PROTOCOL_HEADER_SIZE;
+
+            if !currentPeer.acknowledgements.is_empty() {
+                enet_protocol_send_acknowledgements(host, incomingPeerID);
+            }
+
+            let currentPeer = enet_host_get_mut_peer!(host, incomingPeerID);
+            if checkForTimeouts != 0
+                && !currentPeer.sentReliableCommands.is_empty()
+                && ENET_TIME_GREATER_EQUAL(host.serviceTime, currentPeer.nextTimeout)
+                && enet_protocol_check_timeouts(host, incomingPeerID, event.as_deref_mut()) == 1
+            {
+                if let Some(event) = event.as_deref() {
+                    if event.event_type() != ENetEventType::ENET_EVENT_TYPE_NONE {
+                        return Ok(1);
+                    }
+                }
+
+                let currentPeer = enet_host_get_mut_peer!(host, incomingPeerID);
+                if ((currentPeer.flags as u32) & (ENET_PEER_FLAG_CONTINUE_SENDING as u32)) != 0 {
+                    continueSending = sendPass + 1;
+                }
+
+                continue;
+            }
+
+            let currentPeer = enet_host_get_mut_peer!(host, incomingPeerID);
+            let canPing = if currentPeer.outgoingCommands.is_empty()
+                && currentPeer.outgoingSendReliableCommands.is_empty()
+            {
+                1
+            } else {
+                enet_protocol_check_outgoing_commands(
+                    host,
+                    incomingPeerID,
+                    &mut sentUnreliableCommands,
+                )
+            };
+
+            let currentPeer = enet_host_get_mut_peer!(host, incomingPeerID);
+            if canPing != 0
+                && currentPeer.sentReliableCommands.is_empty()
+                && ENET_TIME_DIFFERENCE(host.serviceTime, currentPeer.lastReceiveTime)
+                    >= currentPeer.pingInterval
+                && (currentPeer.mtu as usize).wrapping_sub(host.packetSize)
+                    >= ENET_PROTOCOL_PING_SIZE
+            {
+                enet_peer_ping(host, incomingPeerID);
+                enet_protocol_check_outgoing_commands(
+                    host,
+                    incomingPeerID,
+                    &mut sentUnreliableCommands,
+                );
+            }
+
+            if host.commandCount != 0 {
+                let currentPeer = enet_host_get_mut_peer!(host, incomingPeerID);
+                if currentPeer.packetLossEpoch == 0 {
+                    currentPeer.packetLossEpoch = host.serviceTime;
+                } else if ENET_TIME_DIFFERENCE(host.serviceTime, currentPeer.packetLossEpoch)
+                    >= ENET_PEER_PACKET_LOSS_INTERVAL
+                    && currentPeer.packetsSent > 0
+                {
+                    let packetLoss =
+                        ((currentPeer.packetsLost as u64 * ENET_PEER_PACKET_LOSS_SCALE as u64)
+                            / currentPeer.packetsSent as u64) as u32;
+
+                    currentPeer.packetLossVariance = (currentPeer.packetLossVariance * 3
+                        + ENET_DIFFERENCE(packetLoss, currentPeer.packetLoss))
+                        / 4;
+                    currentPeer.packetLoss = (currentPeer.packetLoss * 7 + packetLoss) / 8;
+
+                    currentPeer.packetLossEpoch = host.serviceTime;
+                    currentPeer.packetsSent = 0;
+                    currentPeer.packetsLost = 0;
+                }
+
+                let sentLength = enet_protocol_send_datagram(host, incomingPeerID);
+
+                enet_protocol_remove_sent_unreliable_commands(
+                    host,
+                    incomingPeerID,
+                    &mut sentUnreliableCommands,
+                );
+
+                let sentLength = sentLength?;
+
+                host.totalSentData = host.totalSentData.wrapping_add(sentLength as u32);
+                host.totalSentPackets = host.totalSentPackets.wrapping_add(1);
+            }
+
+            let currentPeer = enet_host_get_mut_peer!(host, incomingPeerID);
+            if ((currentPeer.flags as u32) & (ENET_PEER_FLAG_CONTINUE_SENDING as u32)) != 0 {
+                continueSending = sendPass + 1;
+            }
+        }
+
+        sendPass += 1;
+    }
+
+    Ok(0)
+}
+
+pub fn enet_protocol_send_datagram(
+    host: &mut ENetHost,
+    incomingPeerID: u16,
+) -> Result<usize, ENetError> {
+    let bufferPackets = std::mem::replace(&mut host.bufferPackets, std::array::from_fn(|_| None));
+    let packetData: [Option<Ref<Vec<u8>>>; ENET_BUFFER_MAXIMUM as usize] =
+        std::array::from_fn(|i| bufferPackets[i].as_ref().map(|data| data.borrow()));
+
+    let mut commandData = [[0u8; ENET_PROTOCOL_MAXIMUM_COMMAND_SIZE]; ENET_BUFFER_MAXIMUM as usize];
+    let mut payloadOffsets = [None; ENET_BUFFER_MAXIMUM as usize];
+
+    let mut bufferID = 1;
+    for command in host.commands.iter().take(host.commandCount) {
+        enet_protocol_encode_command(command, &mut commandData[bufferID])?;
+        bufferID += 1;
+
+        match command {
+            ENetProtocol::sendReliable(_)
+            | ENetProtocol::sendUnreliable(_)
+            | ENetProtocol::sendUnsequenced(_) => {
+                payloadOffsets[bufferID] = Some(0);
+                bufferID += 1;
+            }
+
+            ENetProtocol::sendFragment(sendFragment) => {
+                payloadOffsets[bufferID] = Some(sendFragment.fragmentOffset as usize);
+                bufferID += 1;
+            }
+
+            _ => {}
+        }
+    }
+
+    let mut headerData = [0u8; ENET_PROTOCOL_HEADER_SIZE + 4];
+    let mut checksumHeaderData = [0u8; 8];
+
+    let mut data: [&[u8]; ENET_BUFFER_MAXIMUM as usize] = [&[]; ENET_BUFFER_MAXIMUM as usize];
+    for i in 1..host.bufferCount {
+        data[i] = match payloadOffsets[i] {
+            Some(offset) => match &packetData[i] {
+                Some(packet) => &packet[offset..],
+                None => &[],
+            },
+
+            None => &commandData[i],
+        };
+    }
+
+    let peer = enet_host_get_mut_peer!(host, incomingPeerID);
+
+    let mut header = ENetProtocolHeader {
+        peerID: 0,
+        sentTime: 0,
+    };
+
+    if ((host.headerFlags as u32) & (ENET_PROTOCOL_HEADER_FLAG_SENT_TIME as u32)) != 0 {
+        header.sentTime = (host.serviceTime & 0xFFFF) as u16;
+        host.buffers[0] = ENetBuffer {
+            dataID: 0,
+            dataLength: ENET_PROTOCOL_HEADER_SIZE,
+        };
+    } else {
+        host.buffers[0] = ENetBuffer {
+            dataID: 0,
+            dataLength: ENET_PROTOCOL_HEADER_SENT_TIME_OFFSET,
+        };
+    }
+
+    let mut shouldCompress = 0;
+    if let (Some(compress), Some(context)) =
+        (host.compressor.compress, host.compressor.context.as_mut())
+    {
+        let originalSize = host.packetSize - 4;
+        let compressedSize = compress(
+            Some(context),
+            &data,
+            &host.buffers[1..host.bufferCount],
+            host.bufferCount - 1,
+            originalSize,
+            &mut host.packetData[1],
+            originalSize,
+        );
+
+        if compressedSize > 0 && compressedSize < originalSize {
+            host.headerFlags |= ENET_PROTOCOL_HEADER_FLAG_COMPRESSED as u16;
+            shouldCompress = compressedSize;
+        }
+    }
+
+    if (peer.outgoingPeerID as u32) < ENET_PROTOCOL_MAXIMUM_PEER_ID {
+        host.headerFlags |=
+            (peer.outgoingSessionID as u16) << (ENET_PROTOCOL_HEADER_SESSION_SHIFT as u16);
+    }
+
+    header.peerID = peer.outgoingPeerID | host.headerFlags;
+    enet_protocol_encode_header(&header, &mut headerData)?;
+
+    if let Some(checksum) = host.checksum {
+        let headerLength = host.buffers[0].dataLength;
+        let connectID = if (peer.outgoingPeerID as u32) < ENET_PROTOCOL_MAXIMUM_PEER_ID {
+            peer.connectID
+        } else {
+            0
+        };
+
+        headerData[headerLength..headerLength + 4].copy_from_slice(&connectID.to_be_bytes());
+        host.buffers[0].dataLength += 4;
+
+        checksumHeaderData.copy_from_slice(&headerData);
+        data[0] = &checksumHeaderData;
+        let newChecksum = checksum(&data, &host.buffers, host.bufferCount);
+
+        headerData[headerLength..headerLength + 4].copy_from_slice(&newChecksum.to_ne_bytes());
+    }
+
+    data[0] = &headerData;
+
+    if shouldCompress > 0 {
+        data[1] = &host.packetData[1];
+        host.buffers[1] = ENetBuffer {
+            dataID: 1,
+            dataLength: shouldCompress,
+        };
+        host.bufferCount = 2;
+    }
+
+    peer.lastSendTime = host.serviceTime;
+
+    crate::c_win32::enet_socket_send(
+        &*host.socket,
+        &peer.address,
+        &data,
+        &host.buffers,
+        host.bufferCount,
+    )
+}
+
+pub fn enet_protocol_read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([data[offset], data[offset + 1]])
+}
+
+pub fn enet_protocol_read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+pub fn enet_protocol_write_u16(data: &mut [u8], offset: usize, value: u16) {
+    data[offset..offset + 2].copy_from_slice(&value.to_be_bytes());
+}
+
+pub fn enet_protocol_write_u32(data: &mut [u8], offset: usize, value: u32) {
+    data[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+}
+
+pub fn enet_protocol_decode_header(
+    data: &[u8],
+) -> Result<(ENetProtocolHeader, usize), ENetProtocolError> {
+    if data.len() < ENET_PROTOCOL_HEADER_SENT_TIME_OFFSET {
+        return Err(ENET_PROTOCOL_ERROR_TRUNCATED);
+    }
+
+    let peerID = enet_protocol_read_u16(data, 0);
+    if (peerID as u32 & ENET_PROTOCOL_HEADER_FLAG_SENT_TIME as u32) == 0 {
+        return Ok((
+            ENetProtocolHeader {
+                peerID,
+                sentTime: 0,
+            },
+            ENET_PROTOCOL_HEADER_SENT_TIME_OFFSET,
+        ));
+    }
+
+    if data.len() < ENET_PROTOCOL_HEADER_SIZE {
+        return Err(ENET_PROTOCOL_ERROR_TRUNCATED);
+    }
+
+    let sentTime = enet_protocol_read_u16(data, ENET_PROTOCOL_HEADER_SENT_TIME_OFFSET);
+
+    Ok((
+        ENetProtocolHeader { peerID, sentTime },
+        ENET_PROTOCOL_HEADER_SIZE,
+    ))
+}
+
+pub fn enet_protocol_encode_header(
+    header: &ENetProtocolHeader,
+    data: &mut [u8],
+) -> Result<usize, ENetProtocolError> {
+    let headerSize = if (header.peerID as u32 & ENET_PROTOCOL_HEADER_FLAG_SENT_TIME as u32) != 0 {
+        ENET_PROTOCOL_HEADER_SIZE
+    } else {
+        ENET_PROTOCOL_HEADER_SENT_TIME_OFFSET
+    };
+
+    if data.len() < headerSize {
+        return Err(ENET_PROTOCOL_ERROR_TRUNCATED);
+    }
+
+    enet_protocol_write_u16(data, 0, header.peerID);
+    if headerSize == ENET_PROTOCOL_HEADER_SIZE {
+        enet_protocol_write_u16(data, ENET_PROTOCOL_HEADER_SENT_TIME_OFFSET, header.sentTime);
+    }
+
+    Ok(headerSize)
+}
+
+pub fn enet_protocol_decode_command_header(
+    data: &[u8],
+) -> Result<ENetProtocolCommandHeader, ENetProtocolError> {
+    if data.len() < ENET_PROTOCOL_COMMAND_HEADER_SIZE {
+        return Err(ENET_PROTOCOL_ERROR_TRUNCATED);
+    }
+
+    Ok(ENetProtocolCommandHeader {
+        command: data[0],
+        channelID: data[1],
+        reliableSequenceNumber: enet_protocol_read_u16(data, 2),
+    })
+}
+
+pub fn enet_protocol_encode_command_header(
+    header: &ENetProtocolCommandHeader,
+    data: &mut [u8],
+) -> Result<usize, ENetProtocolError> {
+    if data.len() < ENET_PROTOCOL_COMMAND_HEADER_SIZE {
+        return Err(ENET_PROTOCOL_ERROR_TRUNCATED);
+    }
+
+    data[0] = header.command;
+    data[1] = header.channelID;
+    enet_protocol_write_u16(data, 2, header.reliableSequenceNumber);
+
+    Ok(ENET_PROTOCOL_COMMAND_HEADER_SIZE)
+}
+
+pub fn enet_protocol_decode_command(data: &[u8]) -> Result<ENetProtocol, ENetProtocolError> {
+    let header = enet_protocol_decode_command_header(data)?;
+
+    let commandSize = enet_protocol_command_size(header.command);
+    if commandSize == 0 {
+        return Err(ENET_PROTOCOL_ERROR_UNKNOWN_COMMAND);
+    }
+
+    if data.len() < commandSize {
+        return Err(ENET_PROTOCOL_ERROR_TRUNCATED);
+    }
+
+    let command = match ENetProtocolCommand::from(header.command) {
+        ENET_PROTOCOL_COMMAND_ACKNOWLEDGE => ENetProtocol::acknowledge(ENetProtocolAcknowledge {
+            header,
+            receivedReliableSequenceNumber: enet_protocol_read_u16(data, 4),
+            receivedSentTime: enet_protocol_read_u16(data, 6),
+        }),
+
+        ENET_PROTOCOL_COMMAND_CONNECT => ENetProtocol::connect(ENetProtocolConnect {
+            header,
+            outgoingPeerID: enet_protocol_read_u16(data, 4),
+            incomingSessionID: data[6],
+            outgoingSessionID: data[7],
+            mtu: enet_protocol_read_u32(data, 8),
+            windowSize: enet_protocol_read_u32(data, 12),
+            channelCount: enet_protocol_read_u32(data, 16),
+            incomingBandwidth: enet_protocol_read_u32(data, 20),
+            outgoingBandwidth: enet_protocol_read_u32(data, 24),
+            packetThrottleInterval: enet_protocol_read_u32(data, 28),
+            packetThrottleAcceleration: enet_protocol_read_u32(data, 32),
+            packetThrottleDeceleration: enet_protocol_read_u32(data, 36),
+            connectID: enet_protocol_read_u32(data, 40),
+            data: enet_protocol_read_u32(data, 44),
+        }),
+
+        ENET_PROTOCOL_COMMAND_VERIFY_CONNECT => {
+            ENetProtocol::verifyConnect(ENetProtocolVerifyConnect {
+                header,
+                outgoingPeerID: enet_protocol_read_u16(data, 4),
+                incomingSessionID: data[6],
+                outgoingSessionID: data[7],
+                mtu: enet_protocol_read_u32(data, 8),
+                windowSize: enet_protocol_read_u32(data, 12),
+                channelCount: enet_protocol_read_u32(data, 16),
+                incomingBandwidth: enet_protocol_read_u32(data, 20),
+                outgoingBandwidth: enet_protocol_read_u32(data, 24),
+                packetThrottleInterval: enet_protocol_read_u32(data, 28),
+                packetThrottleAcceleration: enet_protocol_read_u32(data, 32),
+                packetThrottleDeceleration: enet_protocol_read_u32(data, 36),
+                connectID: enet_protocol_read_u32(data, 40),
+            })
+        }
+
+        ENET_PROTOCOL_COMMAND_DISCONNECT => ENetProtocol::disconnect(ENetProtocolDisconnect {
+            header,
+            data: enet_protocol_read_u32(data, 4),
+        }),
+
+        ENET_PROTOCOL_COMMAND_PING => ENetProtocol::ping(ENetProtocolPing { header }),
+
+        ENET_PROTOCOL_COMMAND_SEND_RELIABLE => {
+            ENetProtocol::sendReliable(ENetProtocolSendReliable {
+                header,
+                dataLength: enet_protocol_read_u16(data, 4),
+            })
+        }
+
+        ENET_PROTOCOL_COMMAND_SEND_UNRELIABLE => {
+            ENetProtocol::sendUnreliable(ENetProtocolSendUnreliable {
+                header,
+                unreliableSequenceNumber: enet_protocol_read_u16(data, 4),
+                dataLength: enet_protocol_read_u16(data, 6),
+            })
+        }
+
+        ENET_PROTOCOL_COMMAND_SEND_UNSEQUENCED => {
+            ENetProtocol::sendUnsequenced(ENetProtocolSendUnsequenced {
+                header,
+                unsequencedGroup: enet_protocol_read_u16(data, 4),
+                dataLength: enet_protocol_read_u16(data, 6),
+            })
+        }
+
+        ENET_PROTOCOL_COMMAND_SEND_FRAGMENT | ENET_PROTOCOL_COMMAND_SEND_UNRELIABLE_FRAGMENT => {
+            ENetProtocol::sendFragment(ENetProtocolSendFragment {
+                header,
+                startSequenceNumber: enet_protocol_read_u16(data, 4),
+                dataLength: enet_protocol_read_u16(data, 6),
+                fragmentCount: enet_protocol_read_u32(data, 8),
+                fragmentNumber: enet_protocol_read_u32(data, 12),
+                totalLength: enet_protocol_read_u32(data, 16),
+                fragmentOffset: enet_protocol_read_u32(data, 20),
+            })
+        }
+
+        ENET_PROTOCOL_COMMAND_BANDWIDTH_LIMIT => {
+            ENetProtocol::bandwidthLimit(ENetProtocolBandwidthLimit {
+                header,
+                incomingBandwidth: enet_protocol_read_u32(data, 4),
+                outgoingBandwidth: enet_protocol_read_u32(data, 8),
+            })
+        }
+
+        ENET_PROTOCOL_COMMAND_THROTTLE_CONFIGURE => {
+            ENetProtocol::throttleConfigure(ENetProtocolThrottleConfigure {
+                header,
+                packetThrottleInterval: enet_protocol_read_u32(data, 4),
+                packetThrottleAcceleration: enet_protocol_read_u32(data, 8),
+                packetThrottleDeceleration: enet_protocol_read_u32(data, 12),
+            })
+        }
+
+        _ => return Err(ENET_PROTOCOL_ERROR_UNKNOWN_COMMAND),
+    };
+
+    Ok(command)
+}
+
+pub fn enet_protocol_encode_command(
+    command: &ENetProtocol,
+    data: &mut [u8],
+) -> Result<usize, ENetProtocolError> {
+    let header = command.command_header();
+
+    let commandSize = match (ENetProtocolCommand::from(header.command), command) {
+        (ENET_PROTOCOL_COMMAND_ACKNOWLEDGE, ENetProtocol::acknowledge(_))
+        | (ENET_PROTOCOL_COMMAND_CONNECT, ENetProtocol::connect(_))
+        | (ENET_PROTOCOL_COMMAND_VERIFY_CONNECT, ENetProtocol::verifyConnect(_))
+        | (ENET_PROTOCOL_COMMAND_DISCONNECT, ENetProtocol::disconnect(_))
+        | (ENET_PROTOCOL_COMMAND_PING, ENetProtocol::ping(_))
+        | (ENET_PROTOCOL_COMMAND_SEND_RELIABLE, ENetProtocol::sendReliable(_))
+        | (ENET_PROTOCOL_COMMAND_SEND_UNRELIABLE, ENetProtocol::sendUnreliable(_))
+        | (ENET_PROTOCOL_COMMAND_SEND_UNSEQUENCED, ENetProtocol::sendUnsequenced(_))
+        | (ENET_PROTOCOL_COMMAND_SEND_FRAGMENT, ENetProtocol::sendFragment(_))
+        | (ENET_PROTOCOL_COMMAND_SEND_UNRELIABLE_FRAGMENT, ENetProtocol::sendFragment(_))
+        | (ENET_PROTOCOL_COMMAND_BANDWIDTH_LIMIT, ENetProtocol::bandwidthLimit(_))
+        | (ENET_PROTOCOL_COMMAND_THROTTLE_CONFIGURE, ENetProtocol::throttleConfigure(_)) => {
+            enet_protocol_command_size(header.command)
+        }
+
+        (ENET_PROTOCOL_COMMAND_NONE, _) => return Err(ENET_PROTOCOL_ERROR_UNKNOWN_COMMAND),
+        _ => return Err(ENET_PROTOCOL_ERROR_COMMAND_MISMATCH),
+    };
+
+    if data.len() < commandSize {
+        return Err(ENET_PROTOCOL_ERROR_TRUNCATED);
+    }
+
+    enet_protocol_encode_command_header(header, data)?;
+
+    match command {
+        ENetProtocol::header(_) | ENetProtocol::ping(_) => {}
+
+        ENetProtocol::acknowledge(acknowledge) => {
+            enet_protocol_write_u16(data, 4, acknowledge.receivedReliableSequenceNumber);
+            enet_protocol_write_u16(data, 6, acknowledge.receivedSentTime);
+        }
+
+        ENetProtocol::connect(connect) => {
+            enet_protocol_write_u16(data, 4, connect.outgoingPeerID);
+            data[6] = connect.incomingSessionID;
+            data[7] = connect.outgoingSessionID;
+            enet_protocol_write_u32(data, 8, connect.mtu);
+            enet_protocol_write_u32(data, 12, connect.windowSize);
+            enet_protocol_write_u32(data, 16, connect.channelCount);
+            enet_protocol_write_u32(data, 20, connect.incomingBandwidth);
+            enet_protocol_write_u32(data, 24, connect.outgoingBandwidth);
+            enet_protocol_write_u32(data, 28, connect.packetThrottleInterval);
+            enet_protocol_write_u32(data, 32, connect.packetThrottleAcceleration);
+            enet_protocol_write_u32(data, 36, connect.packetThrottleDeceleration);
+            enet_protocol_write_u32(data, 40, connect.connectID);
+            enet_protocol_write_u32(data, 44, connect.data);
+        }
+
+        ENetProtocol::verifyConnect(verifyConnect) => {
+            enet_protocol_write_u16(data, 4, verifyConnect.outgoingPeerID);
+            data[6] = verifyConnect.incomingSessionID;
+            data[7] = verifyConnect.outgoingSessionID;
+            enet_protocol_write_u32(data, 8, verifyConnect.mtu);
+            enet_protocol_write_u32(data, 12, verifyConnect.windowSize);
+            enet_protocol_write_u32(data, 16, verifyConnect.channelCount);
+            enet_protocol_write_u32(data, 20, verifyConnect.incomingBandwidth);
+            enet_protocol_write_u32(data, 24, verifyConnect.outgoingBandwidth);
+            enet_protocol_write_u32(data, 28, verifyConnect.packetThrottleInterval);
+            enet_protocol_write_u32(data, 32, verifyConnect.packetThrottleAcceleration);
+            enet_protocol_write_u32(data, 36, verifyConnect.packetThrottleDeceleration);
+            enet_protocol_write_u32(data, 40, verifyConnect.connectID);
+        }
+
+        ENetProtocol::disconnect(disconnect) => {
+            enet_protocol_write_u32(data, 4, disconnect.data);
+        }
+
+        ENetProtocol::sendReliable(sendReliable) => {
+            enet_protocol_write_u16(data, 4, sendReliable.dataLength);
+        }
+
+        ENetProtocol::sendUnreliable(sendUnreliable) => {
+            enet_protocol_write_u16(data, 4, sendUnreliable.unreliableSequenceNumber);
+            enet_protocol_write_u16(data, 6, sendUnreliable.dataLength);
+        }
+
+        ENetProtocol::sendUnsequenced(sendUnsequenced) => {
+            enet_protocol_write_u16(data, 4, sendUnsequenced.unsequencedGroup);
+            enet_protocol_write_u16(data, 6, sendUnsequenced.dataLength);
+        }
+
+        ENetProtocol::sendFragment(sendFragment) => {
+            enet_protocol_write_u16(data, 4, sendFragment.startSequenceNumber);
+            enet_protocol_write_u16(data, 6, sendFragment.dataLength);
+            enet_protocol_write_u32(data, 8, sendFragment.fragmentCount);
+            enet_protocol_write_u32(data, 12, sendFragment.fragmentNumber);
+            enet_protocol_write_u32(data, 16, sendFragment.totalLength);
+            enet_protocol_write_u32(data, 20, sendFragment.fragmentOffset);
+        }
+
+        ENetProtocol::bandwidthLimit(bandwidthLimit) => {
+            enet_protocol_write_u32(data, 4, bandwidthLimit.incomingBandwidth);
+            enet_protocol_write_u32(data, 8, bandwidthLimit.outgoingBandwidth);
+        }
+
+        ENetProtocol::throttleConfigure(throttleConfigure) => {
+            enet_protocol_write_u32(data, 4, throttleConfigure.packetThrottleInterval);
+            enet_protocol_write_u32(data, 8, throttleConfigure.packetThrottleAcceleration);
+            enet_protocol_write_u32(data, 12, throttleConfigure.packetThrottleDeceleration);
+        }
+    }
+
+    Ok(commandSize)
 }
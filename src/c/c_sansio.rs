@@ -0,0 +1,103 @@
+﻿#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(non_camel_case_types)]
+
+use crate::c_host::{enet_host_clock, enet_host_create_with_transport, enet_host_service};
+use crate::h_enet::ENetSocketWait::*;
+use crate::h_enet::{ENetError, ENetEvent, ENetHost, ENetManualClock, ENetTransport};
+use crate::h_sansio::*;
+use std::io;
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+use std::rc::Rc;
+
+pub fn enet_sans_io_create(address: SocketAddr, time: u32) -> ENetSansIO {
+    ENetSansIO {
+        address,
+        clock: ENetManualClock::new(time),
+        incoming: Rc::default(),
+        outgoing: Rc::default(),
+    }
+}
+
+pub fn enet_host_create_sans_io(
+    sansIO: &ENetSansIO,
+    peerCount: usize,
+    channelLimit: usize,
+    incomingBandwidth: u32,
+    outgoingBandwidth: u32,
+) -> Result<ENetHost, ENetError> {
+    let mut host = enet_host_create_with_transport(
+        Box::new(sansIO.clone()),
+        peerCount,
+        channelLimit,
+        incomingBandwidth,
+        outgoingBandwidth,
+    )?;
+
+    enet_host_clock(&mut host, Some(Box::new(sansIO.clock.clone())));
+
+    Ok(host)
+}
+
+pub fn enet_sans_io_receive(sansIO: &ENetSansIO, time: u32, address: SocketAddr, data: &[u8]) {
+    sansIO.clock.set(time);
+    sansIO.incoming.borrow_mut().push_back(ENetDatagram {
+        address,
+        data: data.to_vec(),
+    });
+}
+
+pub fn enet_sans_io_service(
+    sansIO: &ENetSansIO,
+    host: &mut ENetHost,
+    time: u32,
+    event: Option<&mut ENetEvent>,
+) -> Result<i32, ENetError> {
+    sansIO.clock.set(time);
+
+    enet_host_service(host, event, 0)
+}
+
+pub fn enet_sans_io_poll_datagram(sansIO: &ENetSansIO) -> Option<ENetDatagram> {
+    sansIO.outgoing.borrow_mut().pop_front()
+}
+
+impl ENetTransport for ENetSansIO {
+    fn send_to(&self, data: &[u8], address: SocketAddr) -> io::Result<usize> {
+        self.outgoing.borrow_mut().push_back(ENetDatagram {
+            address,
+            data: data.to_vec(),
+        });
+
+        Ok(data.len())
+    }
+
+    fn recv_from(&self, data: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        let datagram = self
+            .incoming
+            .borrow_mut()
+            .pop_front()
+            .ok_or(ErrorKind::WouldBlock)?;
+
+        let length = datagram.data.len().min(data.len());
+        data[..length].copy_from_slice(&datagram.data[..length]);
+
+        Ok((length, datagram.address))
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        Ok(self.address)
+    }
+
+    fn wait(&self, condition: &mut u32, _timeout: u32) -> io::Result<()> {
+        let receive = *condition & (ENET_SOCKET_WAIT_RECEIVE as u32) != 0;
+        *condition = ENET_SOCKET_WAIT_NONE as u32;
+
+        if receive && !self.incoming.borrow().is_empty() {
+            *condition |= ENET_SOCKET_WAIT_RECEIVE as u32;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+﻿#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(non_camel_case_types)]
+
+use crate::h_enet::ENetSocketWait::*;
+use crate::h_enet::{ENetClock, ENetError, ENetManualClock, ENetTransport};
+use crate::h_time::*;
+use crate::h_virtual::*;
+use std::cell::RefCell;
+use std::io;
+use std::io::ErrorKind;
+use std::mem::swap;
+use std::net::SocketAddr;
+use std::rc::Rc;
+
+pub fn enet_virtual_network_create(seed: u64) -> ENetVirtualNetwork {
+    ENetVirtualNetwork {
+        state: Rc::new(RefCell::new(ENetVirtualNetworkState {
+            randomSeed: seed,
+            ..ENetVirtualNetworkState::default()
+        })),
+        clock: ENetManualClock::new(0),
+    }
+}
+
+pub fn enet_virtual_network_set_conditions(
+    network: &ENetVirtualNetwork,
+    conditions: ENetVirtualConditions,
+) {
+    network.state.borrow_mut().conditions = conditions;
+}
+
+pub fn enet_virtual_network_bind(
+    network: &ENetVirtualNetwork,
+    address: SocketAddr,
+) -> Result<ENetVirtualTransport, ENetError> {
+    let mut state = network.state.borrow_mut();
+
+    if state.addresses.contains(&address) {
+        return Err(ENetError::from(io::Error::from(ErrorKind::AddrInUse)));
+    }
+
+    state.addresses.push(address);
+
+    Ok(ENetVirtualTransport {
+        network: network.clone(),
+        address,
+    })
+}
+
+pub fn enet_virtual_network_advance(network: &ENetVirtualNetwork, milliseconds: u32) {
+    network.clock.advance(milliseconds);
+}
+
+pub fn enet_virtual_network_random(state: &mut ENetVirtualNetworkState) -> u64 {
+    state.randomSeed = state.randomSeed.wrapping_add(0x9E3779B97F4A7C15);
+
+    let mut z = state.randomSeed;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+pub fn enet_virtual_network_chance(state: &mut ENetVirtualNetworkState, probability: f64) -> bool {
+    if probability <= 0.0 {
+        return false;
+    }
+
+    ((enet_virtual_network_random(state) >> 11) as f64 / (1u64 << 53) as f64) < probability
+}
+
+pub fn enet_virtual_network_queue(
+    state: &mut ENetVirtualNetworkState,
+    now: u32,
+    from: SocketAddr,
+    to: SocketAddr,
+    data: &[u8],
+) {
+    let conditions = state.conditions;
+
+    let mut deliveryTime = now.wrapping_add(conditions.latency);
+    if conditions.jitter > 0 {
+        let jitter = enet_virtual_network_random(state) % (conditions.jitter as u64 + 1);
+        deliveryTime = deliveryTime.wrapping_add(jitter as u32);
+    }
+
+    let mut datagram = ENetVirtualDatagram {
+        from,
+        to,
+        data: data.to_vec(),
+        deliveryTime,
+        order: state.nextOrder,
+    };
+    state.nextOrder += 1;
+
+    if enet_virtual_network_chance(state, conditions.reorder) {
+        let previous = state
+            .datagrams
+            .iter_mut()
+            .filter(|previous| previous.from == from && previous.to == to)
+            .max_by_key(|previous| previous.order);
+
+        if let Some(previous) = previous {
+            swap(&mut datagram.deliveryTime, &mut previous.deliveryTime);
+            swap(&mut datagram.order, &mut previous.order);
+        }
+    }
+
+    state.datagrams.push(datagram);
+}
+
+pub fn enet_virtual_network_send(
+    network: &ENetVirtualNetwork,
+    from: SocketAddr,
+    to: SocketAddr,
+    data: &[u8],
+) {
+    let now = network.clock.now();
+    let mut state = network.state.borrow_mut();
+    let conditions = state.conditions;
+
+    if conditions.mtu != 0 && data.len() > conditions.mtu {
+        return;
+    }
+
+    if enet_virtual_network_chance(&mut state, conditions.loss) {
+        return;
+    }
+
+    if !state.addresses.contains(&to) {
+        return;
+    }
+
+    enet_virtual_network_queue(&mut state, now, from, to, data);
+
+    if enet_virtual_network_chance(&mut state, conditions.duplicate) {
+        enet_virtual_network_queue(&mut state, now, from, to, data);
+    }
+}
+
+pub fn enet_virtual_network_find_datagram(
+    state: &ENetVirtualNetworkState,
+    now: u32,
+    address: SocketAddr,
+) -> Option<usize> {
+    state
+        .datagrams
+        .iter()
+        .enumerate()
+        .filter(|(_, datagram)| {
+            datagram.to == address && ENET_TIME_LESS_EQUAL(datagram.deliveryTime, now)
+        })
+        .min_by_key(|(_, datagram)| {
+            (
+                !ENET_TIME_DIFFERENCE(now, datagram.deliveryTime),
+                datagram.order,
+            )
+        })
+        .map(|(i, _)| i)
+}
+
+impl ENetTransport for ENetVirtualTransport {
+    fn send_to(&self, data: &[u8], address: SocketAddr) -> io::Result<usize> {
+        enet_virtual_network_send(&self.network, self.address, address, data);
+
+        Ok(data.len())
+    }
+
+    fn recv_from(&self, data: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        let now = self.network.clock.now();
+        let mut state = self.network.state.borrow_mut();
+
+        let i = enet_virtual_network_find_datagram(&state, now, self.address)
+            .ok_or(ErrorKind::WouldBlock)?;
+        let datagram = state.datagrams.swap_remove(i);
+
+        let length = datagram.data.len().min(data.len());
+        data[..length].copy_from_slice(&datagram.data[..length]);
+
+        Ok((length, datagram.from))
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        Ok(self.address)
+    }
+
+    fn wait(&self, condition: &mut u32, _timeout: u32) -> io::Result<()> {
+        let receive = *condition & (ENET_SOCKET_WAIT_RECEIVE as u32) != 0;
+        *condition = ENET_SOCKET_WAIT_NONE as u32;
+
+        let now = self.network.clock.now();
+        if receive
+            && enet_virtual_network_find_datagram(&self.network.state.borrow(), now, self.address)
+                .is_some()
+        {
+            *condition |= ENET_SOCKET_WAIT_RECEIVE as u32;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for ENetVirtualTransport {
+    fn drop(&mut self) {
+        let mut state = self.network.state.borrow_mut();
+        state.addresses.retain(|address| *address != self.address);
+        state
+            .datagrams
+            .retain(|datagram| datagram.to != self.address);
+    }
+}
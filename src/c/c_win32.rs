@@ -3,13 +3,29 @@
 #![allow(non_camel_case_types)]
 
 use crate::h_enet::ENetSocketWait::*;
-use crate::h_enet::{ENetAddress, ENetHostOption, ENetSocketOption};
+use crate::h_enet::{
+    ENetAddress, ENetError, ENetHostOption, ENetSocketOption, ENetTransport, ENET_BUFFER_MAXIMUM,
+};
 use crate::h_system::timeGetTime;
 use crate::h_win32::ENetBuffer;
-use crate::{enet_buffer_as_mut_slice, enet_buffer_as_slice};
-use std::io::Error;
-use std::net::{SocketAddr, UdpSocket};
+use dns_lookup::lookup_addr;
+use socket2::{Domain, Protocol, SockAddr, SockRef, Socket, Type};
+use std::io;
+use std::io::{ErrorKind, IoSlice, IoSliceMut};
+use std::net::{IpAddr, SocketAddr, SocketAddrV4, SocketAddrV6, ToSocketAddrs, UdpSocket};
 use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(unix)]
+use nix::poll::{poll, PollFd, PollFlags, PollTimeout};
+#[cfg(unix)]
+use nix::sys::socket::{recvmsg, MsgFlags, SockaddrStorage};
+#[cfg(unix)]
+use std::os::fd::{AsFd, AsRawFd};
+#[cfg(not(unix))]
+use std::thread;
+#[cfg(not(unix))]
+use std::time::Instant;
 
 pub const SOCKET_ERROR: i32 = -1;
 
@@ -20,220 +36,421 @@ pub fn enet_initialize() {}
 pub fn enet_deinitialize() {}
 
 pub fn enet_host_random_seed() -> u32 {
-    timeGetTime() as u32
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs() as u32)
 }
 
 pub fn enet_time_get() -> u32 {
-    (timeGetTime() as u32) - timeBase.load(Ordering::SeqCst)
+    (timeGetTime() as u32).wrapping_sub(timeBase.load(Ordering::SeqCst))
 }
 
 pub fn enet_time_set(newTimeBase: u32) {
-    timeBase.store((timeGetTime() as u32) - newTimeBase, Ordering::SeqCst);
+    timeBase.store(
+        (timeGetTime() as u32).wrapping_sub(newTimeBase),
+        Ordering::SeqCst,
+    );
 }
 
-pub fn enet_socket_get_address(socket: &UdpSocket, address: &mut ENetAddress) -> Result<(), Error> {
-    match socket.local_addr() {
-        Ok(addr) => {
-            *address = ENetAddress::from(addr);
-            Ok(())
+impl ENetTransport for UdpSocket {
+    fn send_to(&self, data: &[u8], address: SocketAddr) -> io::Result<usize> {
+        UdpSocket::send_to(self, data, address)
+    }
+
+    fn recv_from(&self, data: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        UdpSocket::recv_from(self, data)
+    }
+
+    fn send_vectored_to(&self, data: &[IoSlice], address: SocketAddr) -> io::Result<usize> {
+        SockRef::from(self).send_to_vectored(data, &SockAddr::from(address))
+    }
+
+    #[cfg(unix)]
+    fn recv_vectored_from(&self, data: &mut [IoSliceMut]) -> io::Result<(usize, SocketAddr)> {
+        let message = recvmsg::<SockaddrStorage>(self.as_raw_fd(), data, None, MsgFlags::empty())?;
+
+        let address = message.address.as_ref().and_then(|address| {
+            match (address.as_sockaddr_in(), address.as_sockaddr_in6()) {
+                (Some(address), _) => Some(SocketAddr::V4(SocketAddrV4::from(*address))),
+                (_, Some(address)) => Some(SocketAddr::V6(SocketAddrV6::from(*address))),
+                _ => None,
+            }
+        });
+
+        Ok((
+            message.bytes,
+            address.ok_or_else(|| io::Error::from(ErrorKind::InvalidData))?,
+        ))
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        UdpSocket::local_addr(self)
+    }
+
+    fn wait(&self, condition: &mut u32, timeout: u32) -> io::Result<()> {
+        if *condition & (ENET_SOCKET_WAIT_SEND as u32) != 0 {
+            *condition = ENET_SOCKET_WAIT_NONE as u32;
+            *condition |= ENET_SOCKET_WAIT_SEND as u32;
+            return Ok(());
         }
 
-        Err(e) => Err(e),
+        if *condition & (ENET_SOCKET_WAIT_RECEIVE as u32) != 0 {
+            *condition = ENET_SOCKET_WAIT_NONE as u32;
+
+            match enet_socket_poll(self, timeout) {
+                Ok(true) => {
+                    *condition |= ENET_SOCKET_WAIT_RECEIVE as u32;
+                }
+
+                Ok(false) => {}
+
+                Err(e) if e.kind() == ErrorKind::Interrupted => {
+                    *condition |= ENET_SOCKET_WAIT_INTERRUPT as u32;
+                }
+
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
     }
+
+    fn set_option(&self, option: ENetSocketOption, value: i32) -> io::Result<()> {
+        let socket = SockRef::from(self);
+
+        match option {
+            ENetSocketOption::ENET_SOCKOPT_NONBLOCK => {
+                socket.set_nonblocking(value != 0)?;
+            }
+
+            ENetSocketOption::ENET_SOCKOPT_BROADCAST => {
+                socket.set_broadcast(value != 0)?;
+            }
+
+            ENetSocketOption::ENET_SOCKOPT_RCVBUF => {
+                socket.set_recv_buffer_size(value.max(0) as usize)?;
+            }
+
+            ENetSocketOption::ENET_SOCKOPT_SNDBUF => {
+                socket.set_send_buffer_size(value.max(0) as usize)?;
+            }
+
+            ENetSocketOption::ENET_SOCKOPT_REUSEADDR => {
+                socket.set_reuse_address(value != 0)?;
+            }
+
+            ENetSocketOption::ENET_SOCKOPT_RCVTIMEO => {
+                socket.set_read_timeout(enet_socket_timeout(value))?;
+            }
+
+            ENetSocketOption::ENET_SOCKOPT_SNDTIMEO => {
+                socket.set_write_timeout(enet_socket_timeout(value))?;
+            }
+
+            ENetSocketOption::ENET_SOCKOPT_NODELAY => {
+                socket.set_tcp_nodelay(value != 0)?;
+            }
+
+            ENetSocketOption::ENET_SOCKOPT_TTL => {
+                if socket.local_addr()?.is_ipv6() {
+                    socket.set_unicast_hops_v6(value as u32)?;
+                } else {
+                    socket.set_ttl_v4(value as u32)?;
+                }
+            }
+
+            ENetSocketOption::ENET_SOCKOPT_IPV6_ONLY => {
+                socket.set_only_v6(value != 0)?;
+            }
+
+            ENetSocketOption::ENET_SOCKOPT_ERROR => {
+                return Err(io::Error::from(ErrorKind::InvalidInput));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_option(&self, option: ENetSocketOption) -> io::Result<i32> {
+        let socket = SockRef::from(self);
+
+        let value = match option {
+            ENetSocketOption::ENET_SOCKOPT_NONBLOCK => socket.nonblocking()? as i32,
+            ENetSocketOption::ENET_SOCKOPT_BROADCAST => socket.broadcast()? as i32,
+            ENetSocketOption::ENET_SOCKOPT_RCVBUF => socket.recv_buffer_size()? as i32,
+            ENetSocketOption::ENET_SOCKOPT_SNDBUF => socket.send_buffer_size()? as i32,
+            ENetSocketOption::ENET_SOCKOPT_REUSEADDR => socket.reuse_address()? as i32,
+
+            ENetSocketOption::ENET_SOCKOPT_RCVTIMEO => socket
+                .read_timeout()?
+                .map_or(0, |timeout| timeout.as_millis() as i32),
+
+            ENetSocketOption::ENET_SOCKOPT_SNDTIMEO => socket
+                .write_timeout()?
+                .map_or(0, |timeout| timeout.as_millis() as i32),
+
+            ENetSocketOption::ENET_SOCKOPT_ERROR => socket
+                .take_error()?
+                .and_then(|error| error.raw_os_error())
+                .unwrap_or(0),
+
+            ENetSocketOption::ENET_SOCKOPT_NODELAY => socket.tcp_nodelay()? as i32,
+
+            ENetSocketOption::ENET_SOCKOPT_TTL => {
+                if socket.local_addr()?.is_ipv6() {
+                    socket.unicast_hops_v6()? as i32
+                } else {
+                    socket.ttl_v4()? as i32
+                }
+            }
+
+            ENetSocketOption::ENET_SOCKOPT_IPV6_ONLY => socket.only_v6()? as i32,
+        };
+
+        Ok(value)
+    }
+}
+
+#[cfg(unix)]
+fn enet_socket_poll(socket: &UdpSocket, timeout: u32) -> io::Result<bool> {
+    let mut pollSocket = [PollFd::new(socket.as_fd(), PollFlags::POLLIN)];
+    let timeout = PollTimeout::try_from(timeout).unwrap_or(PollTimeout::MAX);
+
+    Ok(poll(&mut pollSocket, timeout)? > 0)
+}
+
+#[cfg(not(unix))]
+fn enet_socket_poll(socket: &UdpSocket, timeout: u32) -> io::Result<bool> {
+    let deadline = Instant::now() + Duration::from_millis(timeout as u64);
+    let mut buf = [0u8; 1];
+
+    loop {
+        match socket.peek_from(&mut buf) {
+            Ok(_) => return Ok(true),
+            Err(e) if e.kind() == ErrorKind::Interrupted => return Err(e),
+            Err(e) if e.kind() != ErrorKind::WouldBlock => return Ok(true),
+            Err(_) => {}
+        }
+
+        if Instant::now() >= deadline {
+            return Ok(false);
+        }
+
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+pub fn enet_socket_get_address(
+    socket: &dyn ENetTransport,
+    address: &mut ENetAddress,
+) -> Result<(), ENetError> {
+    *address = ENetAddress::from(socket.local_addr()?);
+
+    Ok(())
 }
 
 pub fn enet_socket_create(
     address: &ENetAddress,
     option: ENetHostOption,
-) -> Result<UdpSocket, Error> {
-    match option {
-        ENetHostOption::ENET_HOSTOPT_IPV4 => {
-            UdpSocket::bind::<SocketAddr>((*address).try_parse_ipv4()?)
-        }
+) -> Result<UdpSocket, ENetError> {
+    let (domain, socketAddr) = match option {
+        ENetHostOption::ENET_HOSTOPT_IPV4 => (Domain::IPV4, address.try_parse_ipv4()?),
 
         ENetHostOption::ENET_HOSTOPT_IPV6_ONLY | ENetHostOption::ENET_HOSTOPT_IPV6_DUALMODE => {
-            UdpSocket::bind::<SocketAddr>((*address).parse_ipv6())
+            (Domain::IPV6, address.parse_ipv6())
         }
+    };
+
+    let socket = Socket::new(domain, Type::DGRAM, Some(Protocol::UDP))?;
+
+    match option {
+        ENetHostOption::ENET_HOSTOPT_IPV4 => {}
+        ENetHostOption::ENET_HOSTOPT_IPV6_ONLY => socket.set_only_v6(true)?,
+        ENetHostOption::ENET_HOSTOPT_IPV6_DUALMODE => socket.set_only_v6(false)?,
     }
+
+    socket.bind(&socketAddr.into())?;
+
+    Ok(socket.into())
 }
 
 pub fn enet_socket_set_option(
-    socket: &UdpSocket,
+    socket: &dyn ENetTransport,
     option: ENetSocketOption,
     value: i32,
-) -> Result<(), Error> {
-    match option {
-        ENetSocketOption::ENET_SOCKOPT_NONBLOCK => {
-            socket.set_nonblocking(value != 0)?;
-        }
+) -> Result<(), ENetError> {
+    socket.set_option(option, value)?;
 
-        ENetSocketOption::ENET_SOCKOPT_BROADCAST => {
-            socket.set_broadcast(value != 0)?;
-        }
+    Ok(())
+}
 
-        _ => {}
-    }
+pub fn enet_socket_get_option(
+    socket: &dyn ENetTransport,
+    option: ENetSocketOption,
+    value: &mut i32,
+) -> Result<(), ENetError> {
+    *value = socket.get_option(option)?;
 
     Ok(())
 }
 
+fn enet_socket_timeout(value: i32) -> Option<Duration> {
+    if value > 0 {
+        Some(Duration::from_millis(value as u64))
+    } else {
+        None
+    }
+}
+
 pub fn enet_socket_destroy(socket: UdpSocket) {
     drop(socket);
 }
 
 pub fn enet_socket_send(
-    socket: &UdpSocket,
+    socket: &dyn ENetTransport,
     address: &ENetAddress,
     data: &[&[u8]],
     buffers: &[ENetBuffer],
     bufferCount: usize,
-) -> i32 {
-    match bufferCount {
-        0 => 0,
-
-        1 => {
-            let buffer = enet_buffer_as_slice!(buffers[0], data);
-
-            let result = address.try_parse_any_by_socket(socket);
-            let socketAddr = match result {
-                Ok(x) => x,
-                Err(e) => panic!("{}", e),
-            };
-
-            match socket.send_to::<SocketAddr>(buffer, socketAddr) {
-                Ok(len) => len as i32,
-                Err(_) => -1,
-            }
-        }
+) -> Result<usize, ENetError> {
+    if bufferCount == 0 {
+        return Ok(0);
+    }
 
-        _ => {
-            let merged: Vec<u8> = buffers
-                .iter()
-                .take(bufferCount)
-                .flat_map(|buf| enet_buffer_as_slice!(buf, data))
-                .copied()
-                .collect();
-
-            let result = address.try_parse_any_by_socket(socket);
-            let socketAddr = match result {
-                Ok(x) => x,
-                Err(e) => panic!("{}", e),
-            };
-
-            match socket.send_to::<SocketAddr>(&merged, socketAddr) {
-                Ok(len) => len as i32,
-                Err(_) => -1,
-            }
-        }
+    if bufferCount > ENET_BUFFER_MAXIMUM as usize || bufferCount > buffers.len() {
+        return Err(io::Error::from(ErrorKind::InvalidInput).into());
+    }
+
+    let socketAddr = address.try_parse_any_by_socket(socket)?;
+
+    let mut slices = [IoSlice::new(&[]); ENET_BUFFER_MAXIMUM as usize];
+    for (slice, buffer) in slices.iter_mut().zip(&buffers[..bufferCount]) {
+        let source = data
+            .get(buffer.dataID)
+            .and_then(|source| source.get(..buffer.dataLength))
+            .ok_or_else(|| io::Error::from(ErrorKind::InvalidInput))?;
+        *slice = IoSlice::new(source);
+    }
+
+    match socket.send_vectored_to(&slices[..bufferCount], socketAddr) {
+        Ok(len) => Ok(len),
+        Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(0),
+        Err(e) => Err(ENetError::from(e)),
     }
 }
 
 pub fn enet_socket_receive<'a>(
-    socket: &UdpSocket,
+    socket: &dyn ENetTransport,
     address: &mut ENetAddress,
     data: &'a mut [&'a mut [u8]],
     buffers: &mut [ENetBuffer],
     bufferCount: usize,
-) -> i32 {
-    match bufferCount {
-        0 => 0,
-
-        1 => {
-            let buffer = enet_buffer_as_mut_slice!(buffers[0], data);
-            match socket.recv_from(&mut *buffer) {
-                Ok((len, addr)) => {
-                    *address = ENetAddress::from(addr);
-                    len as i32
-                }
+) -> Result<usize, ENetError> {
+    if bufferCount == 0 {
+        return Ok(0);
+    }
 
-                Err(_) => -1,
-            }
-        }
+    if bufferCount > ENET_BUFFER_MAXIMUM as usize || bufferCount > buffers.len() {
+        return Err(io::Error::from(ErrorKind::InvalidInput).into());
+    }
 
-        _ => {
-            let total_len: usize = buffers
-                .iter()
-                .take(bufferCount)
-                .map(|buf| buf.dataLength)
-                .sum();
-
-            let mut merged = vec![0; total_len];
-
-            match socket.recv_from(&mut merged) {
-                Ok((len, addr)) => {
-                    *address = ENetAddress::from(addr);
-                    let mut offset = 0;
-                    for buf in buffers.iter_mut().take(bufferCount) {
-                        let buffer = enet_buffer_as_mut_slice!(buf, data);
-                        let buf_len = buffer.len();
-                        if offset + buf_len <= len {
-                            buffer.copy_from_slice(&merged[offset..offset + buf_len]);
-                            offset += buf_len;
-                        } else {
-                            let remaining = len - offset;
-                            if remaining > 0 {
-                                buffer[..remaining]
-                                    .copy_from_slice(&merged[offset..offset + remaining]);
-                            }
-
-                            break;
-                        }
-                    }
-
-                    return len as i32;
-                }
+    let mut targets: [Option<&mut [u8]>; ENET_BUFFER_MAXIMUM as usize] =
+        std::array::from_fn(|_| None);
+    for (target, data) in targets.iter_mut().zip(data.iter_mut()) {
+        *target = Some(&mut data[..]);
+    }
 
-                Err(_) => -1,
-            };
+    let mut slices: [IoSliceMut; ENET_BUFFER_MAXIMUM as usize] =
+        std::array::from_fn(|_| IoSliceMut::new(&mut []));
+    for (slice, buffer) in slices.iter_mut().zip(&buffers[..bufferCount]) {
+        let target = targets
+            .get_mut(buffer.dataID)
+            .and_then(Option::take)
+            .and_then(|target| target.get_mut(..buffer.dataLength))
+            .ok_or_else(|| io::Error::from(ErrorKind::InvalidInput))?;
+        *slice = IoSliceMut::new(target);
+    }
 
-            -1
+    match socket.recv_vectored_from(&mut slices[..bufferCount]) {
+        Ok((len, addr)) => {
+            *address = ENetAddress::from(addr);
+            Ok(len)
         }
+
+        Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(0),
+        Err(e) => Err(ENetError::from(e)),
     }
 }
 
-pub fn enet_socket_wait(socket: &UdpSocket, condition: &mut u32) -> bool {
-    if *condition & (ENET_SOCKET_WAIT_SEND as u32) != 0 {
-        *condition = ENET_SOCKET_WAIT_NONE as u32;
-        *condition |= ENET_SOCKET_WAIT_SEND as u32;
-        return true;
-    }
+pub fn enet_socket_wait(
+    socket: &dyn ENetTransport,
+    condition: &mut u32,
+    timeout: u32,
+) -> Result<(), ENetError> {
+    socket.wait(condition, timeout)?;
 
-    if *condition & (ENET_SOCKET_WAIT_RECEIVE as u32) != 0 {
-        let mut buf = [0u8; 1];
-        return match socket.peek(&mut buf) {
-            Ok(_) => {
-                *condition = ENET_SOCKET_WAIT_NONE as u32;
-                *condition |= ENET_SOCKET_WAIT_RECEIVE as u32;
-                true
-            }
+    Ok(())
+}
 
-            Err(_) => false,
-        };
-    }
+pub fn enet_address_set_host_ip(
+    address: &mut ENetAddress,
+    hostName: &str,
+) -> Result<(), ENetError> {
+    let ip: IpAddr = hostName
+        .parse()
+        .map_err(|_| ENetError::ENET_ERROR_INVALID_ADDRESS)?;
+
+    let resolved = ENetAddress::from(SocketAddr::new(ip, address.port));
+    address.host = resolved.host;
+    address.scopeID = resolved.scopeID;
 
-    true
+    Ok(())
 }
 
-pub fn enet_address_get_host_ip(address: &ENetAddress, ip: &mut [u8]) -> bool {
-    let addr: SocketAddr = address.parse_any();
-    let bytes = addr.ip().to_string().into_bytes();
+pub fn enet_address_set_host(
+    address: &mut ENetAddress,
+    hostName: &str,
+    option: ENetHostOption,
+) -> Result<(), ENetError> {
+    let resolved = (hostName, address.port)
+        .to_socket_addrs()?
+        .find(|socketAddr| match option {
+            ENetHostOption::ENET_HOSTOPT_IPV4 => socketAddr.is_ipv4(),
+            ENetHostOption::ENET_HOSTOPT_IPV6_ONLY => socketAddr.is_ipv6(),
+            ENetHostOption::ENET_HOSTOPT_IPV6_DUALMODE => true,
+        })
+        .ok_or(ENetError::ENET_ERROR_ADDRESS_FAMILY)?;
+
+    let resolved = ENetAddress::from(resolved);
+    address.host = resolved.host;
+    address.scopeID = resolved.scopeID;
 
-    if bytes.len() > ip.len() {
-        return false;
+    Ok(())
+}
+
+fn enet_address_copy_name(name: &str, buffer: &mut [u8]) -> Result<(), ENetError> {
+    let bytes = name.as_bytes();
+
+    if bytes.len() >= buffer.len() {
+        return Err(ENetError::ENET_ERROR_BUFFER_TOO_SMALL);
     }
 
-    ip[..bytes.len()].copy_from_slice(&bytes);
-    true
+    buffer[..bytes.len()].copy_from_slice(bytes);
+    buffer[bytes.len()] = 0;
+    Ok(())
 }
 
-pub fn enet_address_get_host(address: &ENetAddress, hostName: &mut [u8]) -> bool {
+pub fn enet_address_get_host_ip(address: &ENetAddress, ip: &mut [u8]) -> Result<(), ENetError> {
     let addr: SocketAddr = address.parse_any();
-    let bytes = addr.ip().to_string().into_bytes();
 
-    if bytes.len() > hostName.len() {
-        return false;
-    }
+    enet_address_copy_name(&addr.ip().to_string(), ip)
+}
+
+pub fn enet_address_get_host(address: &ENetAddress, hostName: &mut [u8]) -> Result<(), ENetError> {
+    let addr: SocketAddr = address.parse_any();
 
-    hostName[..bytes.len()].copy_from_slice(&bytes);
-    true
+    match lookup_addr(&addr.ip()) {
+        Ok(name) => enet_address_copy_name(&name, hostName),
+        Err(_) => enet_address_get_host_ip(address, hostName),
+    }
 }
@@ -2,9 +2,11 @@
 #![allow(non_upper_case_globals)]
 #![allow(non_camel_case_types)]
 
+use std::sync::OnceLock;
 use std::time::Instant;
 
+static timeStart: OnceLock<Instant> = OnceLock::new();
+
 pub fn timeGetTime() -> i64 {
-    let now = Instant::now();
-    now.elapsed().as_millis() as i64
+    timeStart.get_or_init(Instant::now).elapsed().as_millis() as i64
 }
@@ -2,16 +2,19 @@
 #![allow(non_upper_case_globals)]
 #![allow(non_camel_case_types)]
 
+use crate::c_win32::enet_time_get;
 use crate::h_protocol::*;
 use crate::h_win32::ENetBuffer;
-use io::Error;
 use std::any::Any;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::VecDeque;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::io;
-use std::io::ErrorKind;
-use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6, UdpSocket};
+use std::io::{IoSlice, IoSliceMut};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
 use std::rc::Rc;
+use std::str::FromStr;
 
 pub const ENET_VERSION_MAJOR: u32 = 1;
 pub const ENET_VERSION_MINOR: u32 = 3;
@@ -58,6 +61,7 @@ pub enum ENetHostOption {
 }
 
 #[repr(u32)]
+#[derive(Copy, Clone, Eq, PartialEq)]
 pub enum ENetSocketOption {
     ENET_SOCKOPT_NONBLOCK = 1,
     ENET_SOCKOPT_BROADCAST = 2,
@@ -79,12 +83,65 @@ pub enum ENetSocketShutdown {
     ENET_SOCKET_SHUTDOWN_READ_WRITE = 2,
 }
 
+#[derive(Debug)]
+pub enum ENetError {
+    ENET_ERROR_SOCKET(io::Error),
+    ENET_ERROR_ADDRESS_FAMILY,
+    ENET_ERROR_INVALID_ADDRESS,
+    ENET_ERROR_PEER_LIMIT,
+    ENET_ERROR_NOT_CONNECTED,
+    ENET_ERROR_INVALID_CHANNEL,
+    ENET_ERROR_PACKET_TOO_LARGE,
+    ENET_ERROR_BUFFER_TOO_SMALL,
+    ENET_ERROR_PROTOCOL(ENetProtocolError),
+}
+
+impl fmt::Display for ENetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ENetError::ENET_ERROR_SOCKET(e) => write!(f, "socket error: {}", e),
+            ENetError::ENET_ERROR_ADDRESS_FAMILY => {
+                write!(f, "address family not supported by the socket")
+            }
+            ENetError::ENET_ERROR_INVALID_ADDRESS => write!(f, "invalid IP address"),
+            ENetError::ENET_ERROR_PEER_LIMIT => write!(f, "no peer is available"),
+            ENetError::ENET_ERROR_NOT_CONNECTED => write!(f, "peer is not connected"),
+            ENetError::ENET_ERROR_INVALID_CHANNEL => write!(f, "channel is out of range"),
+            ENetError::ENET_ERROR_PACKET_TOO_LARGE => write!(f, "packet is too large"),
+            ENetError::ENET_ERROR_BUFFER_TOO_SMALL => write!(f, "buffer is too small"),
+            ENetError::ENET_ERROR_PROTOCOL(e) => write!(f, "protocol error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ENetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ENetError::ENET_ERROR_SOCKET(e) => Some(e),
+            ENetError::ENET_ERROR_PROTOCOL(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ENetError {
+    fn from(e: io::Error) -> ENetError {
+        ENetError::ENET_ERROR_SOCKET(e)
+    }
+}
+
+impl From<ENetProtocolError> for ENetError {
+    fn from(e: ENetProtocolError) -> ENetError {
+        ENetError::ENET_ERROR_PROTOCOL(e)
+    }
+}
+
 pub const ENET_HOST_ANY: [u8; 16] = [0; 16];
 pub const ENET_HOST_BROADCAST: [u8; 16] =
     [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 255, 255, 255, 255, 255, 255];
 pub const ENET_PORT_ANY: u32 = 0;
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone)]
 pub struct ENetAddress {
     pub host: [u8; 16],
     pub port: u16,
@@ -92,6 +149,7 @@ pub struct ENetAddress {
 }
 
 impl ENetAddress {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> ENetAddress {
         ENetAddress {
             host: [0; 16],
@@ -100,6 +158,26 @@ impl ENetAddress {
         }
     }
 
+    pub fn any(port: u16) -> ENetAddress {
+        ENetAddress {
+            host: ENET_HOST_ANY,
+            port,
+            scopeID: 0,
+        }
+    }
+
+    pub fn broadcast(port: u16) -> ENetAddress {
+        ENetAddress {
+            host: ENET_HOST_BROADCAST,
+            port,
+            scopeID: 0,
+        }
+    }
+
+    pub fn ip(&self) -> IpAddr {
+        self.parse_any().ip()
+    }
+
     pub fn is_ipv4(&self) -> bool {
         self.host[0..10].iter().all(|&b| b == 0) && self.host[10..12] == [0xFF, 0xFF]
     }
@@ -108,26 +186,22 @@ impl ENetAddress {
         !self.is_ipv4()
     }
 
-    pub fn try_parse_any_by_socket(&self, socket: &UdpSocket) -> Result<SocketAddr, Error> {
+    pub fn try_parse_any_by_socket(
+        &self,
+        socket: &dyn ENetTransport,
+    ) -> Result<SocketAddr, ENetError> {
         self.try_parse_any_by_ipv6(socket.local_addr()?.is_ipv6())
     }
 
-    pub fn try_parse_any_by_ipv6(&self, is_ipv6: bool) -> Result<SocketAddr, Error> {
+    pub fn try_parse_any_by_ipv6(&self, is_ipv6: bool) -> Result<SocketAddr, ENetError> {
         match is_ipv6 {
             true => Ok(self.parse_ipv6()),
             false => self.try_parse_ipv4(),
         }
     }
 
-    pub fn try_parse_ipv4(&self) -> Result<SocketAddr, Error> {
-        if self.is_ipv4() {
-            return Ok(self.parse_ipv4());
-        }
-
-        Err(Error::new(
-            ErrorKind::AddrNotAvailable,
-            "AddressFamilyNotSupported",
-        ))
+    pub fn try_parse_ipv4(&self) -> Result<SocketAddr, ENetError> {
+        Ok(SocketAddr::V4(SocketAddrV4::try_from(*self)?))
     }
 
     pub fn parse_any(&self) -> SocketAddr {
@@ -139,14 +213,13 @@ impl ENetAddress {
     }
 
     pub fn parse_ipv4(&self) -> SocketAddr {
-        let bytes: [u8; 4] = self.host[12..16].try_into().unwrap();
-        let ipv4 = Ipv4Addr::from(bytes);
+        let [.., a, b, c, d] = self.host;
+        let ipv4 = Ipv4Addr::new(a, b, c, d);
         SocketAddr::V4(SocketAddrV4::new(ipv4, self.port))
     }
 
     pub fn parse_ipv6(&self) -> SocketAddr {
-        let ipv6 = Ipv6Addr::from(self.host);
-        SocketAddr::V6(SocketAddrV6::new(ipv6, self.port, 0, self.scopeID))
+        SocketAddr::V6(SocketAddrV6::from(*self))
     }
 }
 
@@ -167,16 +240,108 @@ impl From<SocketAddr> for ENetAddress {
 
             SocketAddr::V6(ipv6) => {
                 host.copy_from_slice(&ipv6.ip().octets());
-                ENetAddress {
+                let mut address = ENetAddress {
                     host,
                     port: ipv6.port(),
                     scopeID: ipv6.scope_id(),
+                };
+
+                if address.is_ipv4() {
+                    address.scopeID = 0;
                 }
+
+                address
             }
         }
     }
 }
 
+impl From<IpAddr> for ENetAddress {
+    fn from(ip: IpAddr) -> ENetAddress {
+        ENetAddress::from(SocketAddr::new(ip, 0))
+    }
+}
+
+impl From<ENetAddress> for SocketAddr {
+    fn from(address: ENetAddress) -> SocketAddr {
+        address.parse_any()
+    }
+}
+
+impl TryFrom<ENetAddress> for SocketAddrV4 {
+    type Error = ENetError;
+
+    fn try_from(address: ENetAddress) -> Result<SocketAddrV4, ENetError> {
+        if address.host == ENET_HOST_ANY {
+            return Ok(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, address.port));
+        }
+
+        if !address.is_ipv4() {
+            return Err(ENetError::ENET_ERROR_ADDRESS_FAMILY);
+        }
+
+        let [.., a, b, c, d] = address.host;
+        Ok(SocketAddrV4::new(Ipv4Addr::new(a, b, c, d), address.port))
+    }
+}
+
+impl From<ENetAddress> for SocketAddrV6 {
+    fn from(address: ENetAddress) -> SocketAddrV6 {
+        let ipv6 = Ipv6Addr::from(address.host);
+        let scopeID = if address.is_ipv4() {
+            0
+        } else {
+            address.scopeID
+        };
+        SocketAddrV6::new(ipv6, address.port, 0, scopeID)
+    }
+}
+
+impl FromStr for ENetAddress {
+    type Err = ENetError;
+
+    fn from_str(s: &str) -> Result<ENetAddress, ENetError> {
+        let addr: SocketAddr = s
+            .parse()
+            .map_err(|_| ENetError::ENET_ERROR_INVALID_ADDRESS)?;
+
+        Ok(ENetAddress::from(addr))
+    }
+}
+
+impl fmt::Display for ENetAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.parse_any(), f)
+    }
+}
+
+impl fmt::Debug for ENetAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl PartialEq for ENetAddress {
+    fn eq(&self, other: &ENetAddress) -> bool {
+        self.host == other.host
+            && self.port == other.port
+            && (self.is_ipv4() || self.scopeID == other.scopeID)
+    }
+}
+
+impl Eq for ENetAddress {}
+
+impl Hash for ENetAddress {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.host.hash(state);
+        self.port.hash(state);
+
+        if !self.is_ipv4() {
+            self.scopeID.hash(state);
+        }
+    }
+}
+
 #[repr(u32)]
 pub enum ENetPacketFlag {
     ENET_PACKET_FLAG_RELIABLE = 1 << 0,
@@ -186,7 +351,7 @@ pub enum ENetPacketFlag {
     ENET_PACKET_FLAG_SENT = 1 << 8,
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct ENetPacket {
     pub referenceCount: usize,
     pub flags: u32,
@@ -211,7 +376,7 @@ pub struct ENetOutgoingCommand {
     pub fragmentLength: u16,
     pub sendAttempts: u16,
     pub command: ENetProtocol,
-    pub packet: ENetPacket,
+    pub packet: Option<Rc<RefCell<ENetPacket>>>,
 }
 
 pub struct ENetIncomingCommand {
@@ -225,6 +390,7 @@ pub struct ENetIncomingCommand {
 }
 
 #[repr(u32)]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub enum ENetPeerState {
     ENET_PEER_STATE_DISCONNECTED = 0,
     ENET_PEER_STATE_CONNECTING = 1,
@@ -278,6 +444,22 @@ pub struct ENetChannel {
     pub incomingUnreliableCommands: Vec<ENetIncomingCommand>,
 }
 
+impl ENetChannel {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> ENetChannel {
+        ENetChannel {
+            outgoingReliableSequenceNumber: 0,
+            outgoingUnreliableSequenceNumber: 0,
+            usedReliableWindows: 0,
+            reliableWindows: [0; ENET_PEER_RELIABLE_WINDOWS as usize],
+            incomingReliableSequenceNumber: 0,
+            incomingUnreliableSequenceNumber: 0,
+            incomingReliableCommands: Vec::new(),
+            incomingUnreliableCommands: Vec::new(),
+        }
+    }
+}
+
 #[repr(u32)]
 pub enum ENetPeerFlag {
     ENET_PEER_FLAG_NEEDS_DISPATCH = 1 << 0,
@@ -345,16 +527,26 @@ pub struct ENetPeer {
     pub totalWaitingData: usize,
 }
 
+#[allow(clippy::type_complexity)]
 pub struct ENetCompressor {
     pub context: Option<Box<dyn Any>>,
     pub compress: Option<
-        fn(Option<Box<dyn Any>>, &mut [ENetBuffer], usize, usize, &mut [u8], usize) -> usize,
+        fn(
+            Option<&mut Box<dyn Any>>,
+            &[&[u8]],
+            &[ENetBuffer],
+            usize,
+            usize,
+            &mut [u8],
+            usize,
+        ) -> usize,
     >,
-    pub decompress: Option<fn(Option<&Box<dyn Any>>, &[u8], usize, &mut [u8], usize) -> usize>,
+    pub decompress: Option<fn(Option<&mut Box<dyn Any>>, &[u8], usize, &mut [u8], usize) -> usize>,
     pub destroy: Option<fn(Option<Box<dyn Any>>)>,
 }
 
 impl ENetCompressor {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> ENetCompressor {
         ENetCompressor {
             context: None,
@@ -365,8 +557,92 @@ impl ENetCompressor {
     }
 }
 
+pub trait ENetTransport {
+    fn send_to(&self, data: &[u8], address: SocketAddr) -> io::Result<usize>;
+    fn recv_from(&self, data: &mut [u8]) -> io::Result<(usize, SocketAddr)>;
+    fn local_addr(&self) -> io::Result<SocketAddr>;
+    fn wait(&self, condition: &mut u32, timeout: u32) -> io::Result<()>;
+
+    fn send_vectored_to(&self, data: &[IoSlice], address: SocketAddr) -> io::Result<usize> {
+        match data {
+            [data] => self.send_to(data, address),
+            _ => {
+                let merged: Vec<u8> = data.iter().flat_map(|data| data.iter()).copied().collect();
+                self.send_to(&merged, address)
+            }
+        }
+    }
+
+    fn recv_vectored_from(&self, data: &mut [IoSliceMut]) -> io::Result<(usize, SocketAddr)> {
+        if let [data] = data {
+            return self.recv_from(data);
+        }
+
+        let mut merged = [0u8; ENET_PROTOCOL_MAXIMUM_MTU as usize];
+        let (length, address) = self.recv_from(&mut merged)?;
+
+        let mut offset = 0;
+        for data in data.iter_mut() {
+            let count = data.len().min(length - offset);
+            data[..count].copy_from_slice(&merged[offset..offset + count]);
+            offset += count;
+        }
+
+        Ok((length, address))
+    }
+
+    fn set_option(&self, _option: ENetSocketOption, _value: i32) -> io::Result<()> {
+        Err(io::Error::from(io::ErrorKind::Unsupported))
+    }
+
+    fn get_option(&self, _option: ENetSocketOption) -> io::Result<i32> {
+        Err(io::Error::from(io::ErrorKind::Unsupported))
+    }
+}
+
+pub trait ENetClock {
+    fn now(&self) -> u32;
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct ENetSystemClock;
+
+impl ENetClock for ENetSystemClock {
+    fn now(&self) -> u32 {
+        enet_time_get()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct ENetManualClock {
+    time: Rc<Cell<u32>>,
+}
+
+impl ENetManualClock {
+    pub fn new(time: u32) -> ENetManualClock {
+        ENetManualClock {
+            time: Rc::new(Cell::new(time)),
+        }
+    }
+
+    pub fn set(&self, time: u32) {
+        self.time.set(time);
+    }
+
+    pub fn advance(&self, milliseconds: u32) {
+        self.time.set(self.time.get().wrapping_add(milliseconds));
+    }
+}
+
+impl ENetClock for ENetManualClock {
+    fn now(&self) -> u32 {
+        self.time.get()
+    }
+}
+
+#[allow(clippy::type_complexity)]
 pub struct ENetHost {
-    pub socket: UdpSocket,
+    pub socket: Box<dyn ENetTransport>,
     pub address: ENetAddress,
     pub incomingBandwidth: u32,
     pub outgoingBandwidth: u32,
@@ -385,8 +661,10 @@ pub struct ENetHost {
     pub commandCount: usize,
     pub buffers: [ENetBuffer; ENET_BUFFER_MAXIMUM as usize],
     pub bufferCount: usize,
-    pub checksum: Option<fn(&mut [ENetBuffer], usize) -> u32>,
+    pub bufferPackets: [Option<Rc<RefCell<Vec<u8>>>>; ENET_BUFFER_MAXIMUM as usize],
+    pub checksum: Option<fn(&[&[u8]], &[ENetBuffer], usize) -> u32>,
     pub compressor: ENetCompressor,
+    pub clock: Box<dyn ENetClock>,
     pub packetData: [[u8; ENET_PROTOCOL_MAXIMUM_MTU as usize]; 2],
     pub receivedAddress: ENetAddress,
     pub receivedData: usize,
@@ -395,7 +673,7 @@ pub struct ENetHost {
     pub totalSentPackets: u32,
     pub totalReceivedData: u32,
     pub totalReceivedPackets: u32,
-    pub intercept: Option<fn(&mut ENetHost, &mut ENetEvent) -> i32>,
+    pub intercept: Option<fn(&mut ENetHost, &mut ENetEvent) -> Result<i32, ENetError>>,
     pub connectedPeers: usize,
     pub bandwidthLimitedPeers: usize,
     pub duplicatePeers: usize,
@@ -418,6 +696,7 @@ macro_rules! enet_host_get_mut_peer {
 }
 
 #[repr(u32)]
+#[derive(Copy, Clone, Eq, PartialEq)]
 pub enum ENetEventType {
     ENET_EVENT_TYPE_NONE = 0,
     ENET_EVENT_TYPE_CONNECT = 1,
@@ -451,3 +730,24 @@ pub enum ENetEvent {
         packet: ENetPacket,
     },
 }
+
+impl ENetEvent {
+    pub fn event_type(&self) -> ENetEventType {
+        match self {
+            ENetEvent::NONE { .. } => ENetEventType::ENET_EVENT_TYPE_NONE,
+            ENetEvent::CONNECT { .. } => ENetEventType::ENET_EVENT_TYPE_CONNECT,
+            ENetEvent::DISCONNECT { .. } => ENetEventType::ENET_EVENT_TYPE_DISCONNECT,
+            ENetEvent::RECEIVE { .. } => ENetEventType::ENET_EVENT_TYPE_RECEIVE,
+        }
+    }
+}
+
+impl Default for ENetEvent {
+    fn default() -> Self {
+        ENetEvent::NONE {
+            peer: 0,
+            channelID: 0,
+            data: 0,
+        }
+    }
+}
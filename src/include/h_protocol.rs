@@ -2,6 +2,8 @@
 #![allow(non_upper_case_globals)]
 #![allow(non_camel_case_types)]
 
+use std::fmt;
+
 pub const ENET_PROTOCOL_MINIMUM_MTU: u32 = 576;
 pub const ENET_PROTOCOL_MAXIMUM_MTU: u32 = 4096;
 pub const ENET_PROTOCOL_MAXIMUM_PACKET_COMMANDS: u32 = 32;
@@ -12,7 +14,24 @@ pub const ENET_PROTOCOL_MAXIMUM_CHANNEL_COUNT: u32 = 255;
 pub const ENET_PROTOCOL_MAXIMUM_PEER_ID: u32 = 0xFFF;
 pub const ENET_PROTOCOL_MAXIMUM_FRAGMENT_COUNT: u32 = 1024 * 1024;
 
+pub const ENET_PROTOCOL_HEADER_SIZE: usize = 4;
+pub const ENET_PROTOCOL_HEADER_SENT_TIME_OFFSET: usize = 2;
+pub const ENET_PROTOCOL_COMMAND_HEADER_SIZE: usize = 4;
+pub const ENET_PROTOCOL_ACKNOWLEDGE_SIZE: usize = 8;
+pub const ENET_PROTOCOL_CONNECT_SIZE: usize = 48;
+pub const ENET_PROTOCOL_VERIFY_CONNECT_SIZE: usize = 44;
+pub const ENET_PROTOCOL_DISCONNECT_SIZE: usize = 8;
+pub const ENET_PROTOCOL_PING_SIZE: usize = 4;
+pub const ENET_PROTOCOL_SEND_RELIABLE_SIZE: usize = 6;
+pub const ENET_PROTOCOL_SEND_UNRELIABLE_SIZE: usize = 8;
+pub const ENET_PROTOCOL_SEND_UNSEQUENCED_SIZE: usize = 8;
+pub const ENET_PROTOCOL_SEND_FRAGMENT_SIZE: usize = 24;
+pub const ENET_PROTOCOL_BANDWIDTH_LIMIT_SIZE: usize = 12;
+pub const ENET_PROTOCOL_THROTTLE_CONFIGURE_SIZE: usize = 16;
+pub const ENET_PROTOCOL_MAXIMUM_COMMAND_SIZE: usize = ENET_PROTOCOL_CONNECT_SIZE;
+
 #[repr(u32)]
+#[derive(Copy, Clone, Eq, PartialEq)]
 pub enum ENetProtocolCommand {
     ENET_PROTOCOL_COMMAND_NONE = 0,
     ENET_PROTOCOL_COMMAND_ACKNOWLEDGE = 1,
@@ -32,6 +51,26 @@ pub enum ENetProtocolCommand {
     ENET_PROTOCOL_COMMAND_MASK = 0x0F,
 }
 
+impl From<u8> for ENetProtocolCommand {
+    fn from(command: u8) -> ENetProtocolCommand {
+        match (command as u32) & (ENetProtocolCommand::ENET_PROTOCOL_COMMAND_MASK as u32) {
+            1 => ENetProtocolCommand::ENET_PROTOCOL_COMMAND_ACKNOWLEDGE,
+            2 => ENetProtocolCommand::ENET_PROTOCOL_COMMAND_CONNECT,
+            3 => ENetProtocolCommand::ENET_PROTOCOL_COMMAND_VERIFY_CONNECT,
+            4 => ENetProtocolCommand::ENET_PROTOCOL_COMMAND_DISCONNECT,
+            5 => ENetProtocolCommand::ENET_PROTOCOL_COMMAND_PING,
+            6 => ENetProtocolCommand::ENET_PROTOCOL_COMMAND_SEND_RELIABLE,
+            7 => ENetProtocolCommand::ENET_PROTOCOL_COMMAND_SEND_UNRELIABLE,
+            8 => ENetProtocolCommand::ENET_PROTOCOL_COMMAND_SEND_FRAGMENT,
+            9 => ENetProtocolCommand::ENET_PROTOCOL_COMMAND_SEND_UNSEQUENCED,
+            10 => ENetProtocolCommand::ENET_PROTOCOL_COMMAND_BANDWIDTH_LIMIT,
+            11 => ENetProtocolCommand::ENET_PROTOCOL_COMMAND_THROTTLE_CONFIGURE,
+            12 => ENetProtocolCommand::ENET_PROTOCOL_COMMAND_SEND_UNRELIABLE_FRAGMENT,
+            _ => ENetProtocolCommand::ENET_PROTOCOL_COMMAND_NONE,
+        }
+    }
+}
+
 #[repr(u32)]
 pub enum ENetProtocolFlag {
     ENET_PROTOCOL_COMMAND_FLAG_ACKNOWLEDGE = 1 << 7,
@@ -46,6 +85,29 @@ pub enum ENetProtocolFlag {
     ENET_PROTOCOL_HEADER_SESSION_SHIFT = 12,
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ENetProtocolError {
+    ENET_PROTOCOL_ERROR_TRUNCATED,
+    ENET_PROTOCOL_ERROR_UNKNOWN_COMMAND,
+    ENET_PROTOCOL_ERROR_COMMAND_MISMATCH,
+}
+
+impl fmt::Display for ENetProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ENetProtocolError::ENET_PROTOCOL_ERROR_TRUNCATED => write!(f, "truncated data"),
+            ENetProtocolError::ENET_PROTOCOL_ERROR_UNKNOWN_COMMAND => {
+                write!(f, "unknown command")
+            }
+            ENetProtocolError::ENET_PROTOCOL_ERROR_COMMAND_MISMATCH => {
+                write!(f, "command does not match its number")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ENetProtocolError {}
+
 #[derive(Copy, Clone, Default)]
 pub struct ENetProtocolHeader {
     pub peerID: u16,
@@ -174,6 +236,42 @@ pub enum ENetProtocol {
     throttleConfigure(ENetProtocolThrottleConfigure),
 }
 
+impl ENetProtocol {
+    pub fn command_header(&self) -> &ENetProtocolCommandHeader {
+        match self {
+            ENetProtocol::header(header) => header,
+            ENetProtocol::acknowledge(command) => &command.header,
+            ENetProtocol::connect(command) => &command.header,
+            ENetProtocol::verifyConnect(command) => &command.header,
+            ENetProtocol::disconnect(command) => &command.header,
+            ENetProtocol::ping(command) => &command.header,
+            ENetProtocol::sendReliable(command) => &command.header,
+            ENetProtocol::sendUnreliable(command) => &command.header,
+            ENetProtocol::sendUnsequenced(command) => &command.header,
+            ENetProtocol::sendFragment(command) => &command.header,
+            ENetProtocol::bandwidthLimit(command) => &command.header,
+            ENetProtocol::throttleConfigure(command) => &command.header,
+        }
+    }
+
+    pub fn command_header_mut(&mut self) -> &mut ENetProtocolCommandHeader {
+        match self {
+            ENetProtocol::header(header) => header,
+            ENetProtocol::acknowledge(command) => &mut command.header,
+            ENetProtocol::connect(command) => &mut command.header,
+            ENetProtocol::verifyConnect(command) => &mut command.header,
+            ENetProtocol::disconnect(command) => &mut command.header,
+            ENetProtocol::ping(command) => &mut command.header,
+            ENetProtocol::sendReliable(command) => &mut command.header,
+            ENetProtocol::sendUnreliable(command) => &mut command.header,
+            ENetProtocol::sendUnsequenced(command) => &mut command.header,
+            ENetProtocol::sendFragment(command) => &mut command.header,
+            ENetProtocol::bandwidthLimit(command) => &mut command.header,
+            ENetProtocol::throttleConfigure(command) => &mut command.header,
+        }
+    }
+}
+
 impl Default for ENetProtocol {
     fn default() -> Self {
         ENetProtocol::header(ENetProtocolCommandHeader::default())
@@ -0,0 +1,22 @@
+﻿#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(non_camel_case_types)]
+
+use crate::h_enet::ENetManualClock;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::rc::Rc;
+
+pub struct ENetDatagram {
+    pub address: SocketAddr,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone)]
+pub struct ENetSansIO {
+    pub address: SocketAddr,
+    pub clock: ENetManualClock,
+    pub incoming: Rc<RefCell<VecDeque<ENetDatagram>>>,
+    pub outgoing: Rc<RefCell<VecDeque<ENetDatagram>>>,
+}
@@ -18,6 +18,7 @@ pub const fn ENET_MIN(x: u32, y: u32) -> u32 {
     }
 }
 
+#[allow(clippy::manual_abs_diff)]
 pub const fn ENET_DIFFERENCE(x: u32, y: u32) -> u32 {
     if x < y {
         y - x
@@ -0,0 +1,46 @@
+﻿#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(non_camel_case_types)]
+
+use crate::h_enet::ENetManualClock;
+use std::cell::RefCell;
+use std::net::SocketAddr;
+use std::rc::Rc;
+
+#[derive(Copy, Clone, Default, Debug)]
+pub struct ENetVirtualConditions {
+    pub latency: u32,
+    pub jitter: u32,
+    pub loss: f64,
+    pub duplicate: f64,
+    pub reorder: f64,
+    pub mtu: usize,
+}
+
+pub struct ENetVirtualDatagram {
+    pub from: SocketAddr,
+    pub to: SocketAddr,
+    pub data: Vec<u8>,
+    pub deliveryTime: u32,
+    pub order: u64,
+}
+
+#[derive(Default)]
+pub struct ENetVirtualNetworkState {
+    pub conditions: ENetVirtualConditions,
+    pub randomSeed: u64,
+    pub nextOrder: u64,
+    pub addresses: Vec<SocketAddr>,
+    pub datagrams: Vec<ENetVirtualDatagram>,
+}
+
+#[derive(Clone)]
+pub struct ENetVirtualNetwork {
+    pub state: Rc<RefCell<ENetVirtualNetworkState>>,
+    pub clock: ENetManualClock,
+}
+
+pub struct ENetVirtualTransport {
+    pub network: ENetVirtualNetwork,
+    pub address: SocketAddr,
+}
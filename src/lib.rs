@@ -6,8 +6,10 @@ mod include {
     pub mod h_compress;
     pub mod h_enet;
     pub mod h_protocol;
+    pub mod h_sansio;
     pub mod h_time;
     pub mod h_utility;
+    pub mod h_virtual;
     pub mod h_win32;
 }
 
@@ -17,6 +19,8 @@ mod c {
     pub mod c_packet;
     pub mod c_peer;
     pub mod c_protocol;
+    pub mod c_sansio;
+    pub mod c_virtual;
     pub mod c_win32;
 }
 
@@ -25,8 +29,10 @@ pub use define::h_system;
 pub use include::h_compress;
 pub use include::h_enet;
 pub use include::h_protocol;
+pub use include::h_sansio;
 pub use include::h_time;
 pub use include::h_utility;
+pub use include::h_virtual;
 pub use include::h_win32;
 
 pub use c::c_compress;
@@ -34,4 +40,6 @@ pub use c::c_host;
 pub use c::c_packet;
 pub use c::c_peer;
 pub use c::c_protocol;
+pub use c::c_sansio;
+pub use c::c_virtual;
 pub use c::c_win32;
@@ -0,0 +1,101 @@
+#![allow(non_snake_case)]
+
+//! Conversions between `ENetAddress`, strings and the std address types.
+
+use enet_rust::c_host::enet_host_create;
+use enet_rust::h_enet::*;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+#[test]
+fn display_brackets_ipv6_and_shows_the_scope() {
+    let ipv4: ENetAddress = "192.0.2.1:7777".parse().unwrap();
+    assert_eq!(ipv4.to_string(), "192.0.2.1:7777");
+
+    let ipv6: ENetAddress = "[2001:db8::1]:7777".parse().unwrap();
+    assert_eq!(ipv6.to_string(), "[2001:db8::1]:7777");
+
+    let scoped = ENetAddress::from(SocketAddr::V6(SocketAddrV6::new(
+        "fe80::1".parse().unwrap(),
+        7777,
+        0,
+        3,
+    )));
+    assert_eq!(scoped.to_string(), "[fe80::1%3]:7777");
+    assert_eq!(format!("{scoped:?}"), "[fe80::1%3]:7777");
+}
+
+#[test]
+fn from_str_round_trips_and_rejects_garbage() {
+    for text in ["127.0.0.1:1", "[::1]:65535", "[fe80::1%2]:80"] {
+        let address: ENetAddress = text.parse().unwrap();
+        assert_eq!(address.to_string(), text);
+        assert_eq!(address.to_string().parse::<ENetAddress>().unwrap(), address);
+    }
+
+    for text in ["", "127.0.0.1", "localhost:80", "::1:80", "127.0.0.1:65536"] {
+        assert!(matches!(
+            text.parse::<ENetAddress>(),
+            Err(ENetError::ENET_ERROR_INVALID_ADDRESS)
+        ));
+    }
+}
+
+#[test]
+fn std_conversions() {
+    let ipv4: ENetAddress = "192.0.2.1:7777".parse().unwrap();
+    assert_eq!(
+        SocketAddrV4::try_from(ipv4).unwrap(),
+        SocketAddrV4::new(Ipv4Addr::new(192, 0, 2, 1), 7777)
+    );
+    assert_eq!(
+        SocketAddrV6::from(ipv4),
+        SocketAddrV6::new(Ipv4Addr::new(192, 0, 2, 1).to_ipv6_mapped(), 7777, 0, 0)
+    );
+    assert_eq!(SocketAddr::from(ipv4), "192.0.2.1:7777".parse().unwrap());
+
+    let ipv6: ENetAddress = "[2001:db8::1]:7777".parse().unwrap();
+    assert!(matches!(
+        SocketAddrV4::try_from(ipv6),
+        Err(ENetError::ENET_ERROR_ADDRESS_FAMILY)
+    ));
+
+    let ip = IpAddr::V6(Ipv6Addr::LOCALHOST);
+    let address = ENetAddress::from(ip);
+    assert_eq!(address.ip(), ip);
+    assert_eq!(address.port, 0);
+
+    let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    assert_eq!(ENetAddress::from(ip).ip(), ip);
+}
+
+#[test]
+fn any_and_broadcast_constructors() {
+    let any = ENetAddress::any(7777);
+    assert_eq!(any.host, ENET_HOST_ANY);
+    assert_eq!(any.to_string(), "[::]:7777");
+
+    let broadcast = ENetAddress::broadcast(7777);
+    assert_eq!(broadcast.host, ENET_HOST_BROADCAST);
+    assert_eq!(broadcast.ip(), IpAddr::V4(Ipv4Addr::BROADCAST));
+    assert_eq!(broadcast.to_string(), "255.255.255.255:7777");
+}
+
+#[test]
+fn any_binds_an_ipv4_host() {
+    assert_eq!(
+        SocketAddrV4::try_from(ENetAddress::any(7777)).unwrap(),
+        SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 7777)
+    );
+
+    let host = enet_host_create(
+        &ENetAddress::any(0),
+        1,
+        1,
+        0,
+        0,
+        ENetHostOption::ENET_HOSTOPT_IPV4,
+    )
+    .unwrap();
+    assert_eq!(host.address.ip(), IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+    assert_ne!(host.address.port, 0);
+}
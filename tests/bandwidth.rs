@@ -0,0 +1,92 @@
+#![allow(non_snake_case)]
+
+//! Host bandwidth limits and the periodic bandwidth throttle, run over an
+//! `ENetVirtualNetwork`.
+
+use enet_rust::c_host::*;
+use enet_rust::c_packet::enet_packet_create;
+use enet_rust::c_peer::enet_peer_send;
+use enet_rust::c_virtual::*;
+use enet_rust::h_enet::ENetPacketFlag::*;
+use enet_rust::h_enet::*;
+use enet_rust::h_virtual::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn host(
+    network: &ENetVirtualNetwork,
+    address: &str,
+    peerCount: usize,
+    outgoingBandwidth: u32,
+) -> ENetHost {
+    let transport = enet_virtual_network_bind(network, address.parse().unwrap()).unwrap();
+    let mut host =
+        enet_host_create_with_transport(Box::new(transport), peerCount, 1, 0, outgoingBandwidth)
+            .unwrap();
+    enet_host_clock(&mut host, Some(Box::new(network.clock.clone())));
+    host
+}
+
+fn run(network: &ENetVirtualNetwork, hosts: &mut [&mut ENetHost], milliseconds: u32) {
+    for _ in 0..milliseconds / 10 {
+        for host in hosts.iter_mut() {
+            let mut event = ENetEvent::default();
+            while enet_host_service(host, Some(&mut event), 0).unwrap() > 0 {}
+        }
+        enet_virtual_network_advance(network, 10);
+    }
+}
+
+#[test]
+fn bandwidth_limit_reaches_every_peer_and_throttles_outgoing_data() {
+    let network = enet_virtual_network_create(5);
+    enet_virtual_network_set_conditions(
+        &network,
+        ENetVirtualConditions {
+            latency: 5,
+            ..ENetVirtualConditions::default()
+        },
+    );
+
+    let mut server = host(&network, "10.0.0.1:7777", 2, 0);
+    let mut first = host(&network, "10.0.0.2:7777", 1, 3000);
+    let mut second = host(&network, "10.0.0.3:7777", 1, 3000);
+    enet_host_connect(&mut first, &server.address, 1, 0).unwrap();
+    enet_host_connect(&mut second, &server.address, 1, 0).unwrap();
+
+    run(&network, &mut [&mut server, &mut first, &mut second], 100);
+    assert_eq!(server.connectedPeers, 2);
+
+    enet_host_bandwidth_limit(&mut server, 4000, 1000);
+
+    for peerID in 0..2 {
+        for _ in 0..4 {
+            let packet = enet_packet_create(
+                Rc::new(RefCell::new(vec![0; 500])),
+                500,
+                ENET_PACKET_FLAG_RELIABLE as u32,
+            );
+            enet_peer_send(&mut server, peerID, 0, packet).unwrap();
+        }
+    }
+
+    for peer in &server.peers {
+        assert_eq!(peer.packetThrottleLimit, ENET_PEER_PACKET_THROTTLE_SCALE);
+    }
+
+    run(
+        &network,
+        &mut [&mut server, &mut first, &mut second],
+        ENET_HOST_BANDWIDTH_THROTTLE_INTERVAL + 100,
+    );
+
+    for client in [&first, &second] {
+        assert_eq!(client.peers[0].incomingBandwidth, 2000);
+        assert_eq!(client.peers[0].outgoingBandwidth, 1000);
+    }
+
+    for peer in &server.peers {
+        assert!(peer.packetThrottleLimit < ENET_PEER_PACKET_THROTTLE_SCALE);
+        assert!(peer.packetThrottle <= peer.packetThrottleLimit);
+    }
+}
@@ -0,0 +1,105 @@
+#![allow(non_snake_case)]
+
+//! The CONNECT / VERIFY_CONNECT handshake, stepped one datagram at a time
+//! through the sans-IO core.
+
+use enet_rust::c_host::*;
+use enet_rust::c_protocol::*;
+use enet_rust::c_sansio::*;
+use enet_rust::h_enet::ENetPeerState::*;
+use enet_rust::h_enet::*;
+use enet_rust::h_protocol::ENetProtocolCommand::*;
+use enet_rust::h_sansio::*;
+
+fn service(sansIO: &ENetSansIO, host: &mut ENetHost, time: u32) -> Vec<ENetEvent> {
+    let mut events = Vec::new();
+
+    loop {
+        let mut event = ENetEvent::default();
+        if enet_sans_io_service(sansIO, host, time, Some(&mut event)).unwrap() == 0 {
+            return events;
+        }
+
+        events.push(event);
+    }
+}
+
+fn commands(data: &[u8]) -> Vec<u8> {
+    let (_, mut offset) = enet_protocol_decode_header(data).unwrap();
+    let mut commands = Vec::new();
+
+    while offset < data.len() {
+        let command = enet_protocol_decode_command(&data[offset..]).unwrap();
+        let header = command.command_header();
+        commands.push(header.command & ENET_PROTOCOL_COMMAND_MASK as u8);
+        offset += enet_protocol_command_size(header.command);
+    }
+
+    commands
+}
+
+fn forward(from: &ENetSansIO, to: &ENetSansIO, time: u32) -> Vec<u8> {
+    let datagram = enet_sans_io_poll_datagram(from).unwrap();
+    assert!(enet_sans_io_poll_datagram(from).is_none());
+    assert_eq!(datagram.address, to.address);
+
+    enet_sans_io_receive(to, time, from.address, &datagram.data);
+    commands(&datagram.data)
+}
+
+#[test]
+fn handshake_connects_both_peers_with_matching_parameters() {
+    let server = enet_sans_io_create("10.0.0.1:7777".parse().unwrap(), 0);
+    let client = enet_sans_io_create("10.0.0.2:7777".parse().unwrap(), 0);
+    let mut serverHost = enet_host_create_sans_io(&server, 4, 2, 0, 0).unwrap();
+    let mut clientHost = enet_host_create_sans_io(&client, 1, 2, 0, 0).unwrap();
+
+    let peer = enet_host_connect(&mut clientHost, &serverHost.address, 2, 42).unwrap();
+    assert!(clientHost.peers[peer as usize].state == ENET_PEER_STATE_CONNECTING);
+
+    assert!(service(&client, &mut clientHost, 0).is_empty());
+    assert_eq!(
+        forward(&client, &server, 10),
+        [ENET_PROTOCOL_COMMAND_CONNECT as u8]
+    );
+
+    assert!(service(&server, &mut serverHost, 10).is_empty());
+    assert!(serverHost.peers[0].state == ENET_PEER_STATE_ACKNOWLEDGING_CONNECT);
+    assert_eq!(
+        forward(&server, &client, 20),
+        [ENET_PROTOCOL_COMMAND_VERIFY_CONNECT as u8]
+    );
+
+    match service(&client, &mut clientHost, 20).as_slice() {
+        [ENetEvent::CONNECT {
+            peer: eventPeer,
+            data: 0,
+            ..
+        }] => assert_eq!(*eventPeer, peer),
+        _ => panic!("expected a connect event on the client"),
+    }
+    assert!(clientHost.peers[peer as usize].state == ENET_PEER_STATE_CONNECTED);
+    assert_eq!(
+        forward(&client, &server, 30),
+        [ENET_PROTOCOL_COMMAND_ACKNOWLEDGE as u8]
+    );
+
+    match service(&server, &mut serverHost, 30).as_slice() {
+        [ENetEvent::CONNECT {
+            peer: 0, data: 42, ..
+        }] => {}
+        _ => panic!("expected a connect event carrying the data word on the server"),
+    }
+    assert!(serverHost.peers[0].state == ENET_PEER_STATE_CONNECTED);
+
+    let serverPeer = &serverHost.peers[0];
+    let clientPeer = &clientHost.peers[peer as usize];
+    assert_eq!(serverPeer.connectID, clientPeer.connectID);
+    assert_eq!(serverPeer.outgoingPeerID, peer);
+    assert_eq!(clientPeer.outgoingPeerID, 0);
+    assert_eq!(serverPeer.channelCount, 2);
+    assert_eq!(clientPeer.channelCount, 2);
+    assert_eq!(serverPeer.mtu, clientPeer.mtu);
+    assert_eq!(serverHost.connectedPeers, 1);
+    assert_eq!(clientHost.connectedPeers, 1);
+}
@@ -0,0 +1,145 @@
+#![allow(non_snake_case)]
+
+//! Graceful, deferred and immediate disconnects between two hosts on an
+//! `ENetVirtualNetwork`.
+
+use enet_rust::c_host::*;
+use enet_rust::c_packet::enet_packet_create;
+use enet_rust::c_peer::*;
+use enet_rust::c_protocol::enet_protocol_receive_incoming_commands;
+use enet_rust::c_virtual::*;
+use enet_rust::h_enet::ENetPacketFlag::*;
+use enet_rust::h_enet::ENetPeerState::*;
+use enet_rust::h_enet::*;
+use enet_rust::h_virtual::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn host(network: &ENetVirtualNetwork, address: &str) -> ENetHost {
+    let transport = enet_virtual_network_bind(network, address.parse().unwrap()).unwrap();
+    let mut host = enet_host_create_with_transport(Box::new(transport), 1, 1, 0, 0).unwrap();
+    enet_host_clock(&mut host, Some(Box::new(network.clock.clone())));
+    host
+}
+
+fn service(host: &mut ENetHost) -> Vec<String> {
+    let mut events = Vec::new();
+
+    loop {
+        let mut event = ENetEvent::default();
+        if enet_host_service(host, Some(&mut event), 0).unwrap() == 0 {
+            return events;
+        }
+
+        match event {
+            ENetEvent::CONNECT { .. } => events.push("connect".to_string()),
+            ENetEvent::DISCONNECT { data, .. } => events.push(format!("disconnect {data}")),
+            ENetEvent::RECEIVE { packet, .. } => {
+                let data = packet.data.as_ref().unwrap().borrow()[..].to_vec();
+                events.push(format!("receive {data:?}"));
+            }
+            ENetEvent::NONE { .. } => {}
+        }
+    }
+}
+
+fn connect() -> (ENetVirtualNetwork, ENetHost, ENetHost, u16) {
+    let network = enet_virtual_network_create(1);
+    enet_virtual_network_set_conditions(
+        &network,
+        ENetVirtualConditions {
+            latency: 5,
+            ..ENetVirtualConditions::default()
+        },
+    );
+
+    let mut server = host(&network, "10.0.0.1:7777");
+    let mut client = host(&network, "10.0.0.2:7777");
+    let peer = enet_host_connect(&mut client, &server.address, 1, 0).unwrap();
+
+    for _ in 0..10 {
+        service(&mut server);
+        service(&mut client);
+        enet_virtual_network_advance(&network, 10);
+    }
+
+    assert!(server.peers[0].state == ENET_PEER_STATE_CONNECTED);
+    assert!(client.peers[peer as usize].state == ENET_PEER_STATE_CONNECTED);
+
+    (network, server, client, peer)
+}
+
+#[test]
+fn disconnect_later_delivers_queued_packets_before_the_disconnect() {
+    let (network, mut server, mut client, peer) = connect();
+
+    for i in 0..3u8 {
+        let packet = enet_packet_create(
+            Rc::new(RefCell::new(vec![i; 4])),
+            4,
+            ENET_PACKET_FLAG_RELIABLE as u32,
+        );
+        enet_peer_send(&mut client, peer, 0, packet).unwrap();
+    }
+
+    enet_peer_disconnect_later(&mut client, peer, 42);
+    assert!(client.peers[peer as usize].state == ENET_PEER_STATE_DISCONNECT_LATER);
+
+    let mut serverEvents = Vec::new();
+    let mut clientEvents = Vec::new();
+
+    for _ in 0..100 {
+        clientEvents.extend(service(&mut client));
+        enet_virtual_network_advance(&network, 10);
+
+        enet_protocol_receive_incoming_commands(&mut server, None).unwrap();
+        if server.peers[0].state == ENET_PEER_STATE_ACKNOWLEDGING_DISCONNECT {
+            break;
+        }
+        serverEvents.extend(service(&mut server));
+    }
+
+    assert!(server.peers[0].state == ENET_PEER_STATE_ACKNOWLEDGING_DISCONNECT);
+
+    enet_host_flush(&mut server).unwrap();
+    assert!(server.peers[0].state == ENET_PEER_STATE_ZOMBIE);
+
+    serverEvents.extend(service(&mut server));
+    assert!(server.peers[0].state == ENET_PEER_STATE_DISCONNECTED);
+    assert_eq!(
+        serverEvents,
+        [
+            "receive [0, 0, 0, 0]",
+            "receive [1, 1, 1, 1]",
+            "receive [2, 2, 2, 2]",
+            "disconnect 42",
+        ]
+    );
+
+    for _ in 0..10 {
+        enet_virtual_network_advance(&network, 10);
+        clientEvents.extend(service(&mut client));
+    }
+
+    assert_eq!(clientEvents, ["disconnect 0"]);
+    assert!(client.peers[peer as usize].state == ENET_PEER_STATE_DISCONNECTED);
+}
+
+#[test]
+fn disconnect_now_frees_the_peer_without_waiting_for_a_reply() {
+    let (network, mut server, mut client, peer) = connect();
+
+    enet_peer_disconnect_now(&mut client, peer, 7);
+    assert!(client.peers[peer as usize].state == ENET_PEER_STATE_DISCONNECTED);
+    assert_eq!(client.connectedPeers, 0);
+
+    enet_virtual_network_advance(&network, 10);
+    assert!(service(&mut client).is_empty());
+    assert_eq!(service(&mut server), ["disconnect 7"]);
+    assert!(server.peers[0].state == ENET_PEER_STATE_DISCONNECTED);
+
+    assert_eq!(
+        enet_host_connect(&mut client, &server.address, 1, 0).unwrap(),
+        peer
+    );
+}
@@ -0,0 +1,87 @@
+#![allow(non_snake_case)]
+
+//! IPv6 host options set IPV6_V6ONLY explicitly, and IPv4 peers reached
+//! through a dual-stack socket keep a single address identity.
+
+use enet_rust::c_host::*;
+use enet_rust::c_win32::*;
+use enet_rust::h_enet::ENetSocketOption::ENET_SOCKOPT_IPV6_ONLY;
+use enet_rust::h_enet::*;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6};
+
+fn ipv6_any() -> ENetAddress {
+    ENetAddress::from(SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)))
+}
+
+fn ipv4_loopback(port: u16) -> ENetAddress {
+    ENetAddress::from(SocketAddr::from((Ipv4Addr::LOCALHOST, port)))
+}
+
+#[test]
+fn ipv6_options_set_v6only_explicitly() {
+    for (option, expected) in [
+        (ENetHostOption::ENET_HOSTOPT_IPV6_ONLY, 1),
+        (ENetHostOption::ENET_HOSTOPT_IPV6_DUALMODE, 0),
+    ] {
+        let socket = enet_socket_create(&ipv6_any(), option).unwrap();
+
+        let mut value = -1;
+        enet_socket_get_option(&socket, ENET_SOCKOPT_IPV6_ONLY, &mut value).unwrap();
+        assert_eq!(value, expected);
+    }
+}
+
+#[test]
+fn v4_mapped_addresses_compare_equal_to_ipv4() {
+    let mapped = SocketAddrV6::new(Ipv4Addr::LOCALHOST.to_ipv6_mapped(), 7777, 5, 3);
+    let address = ENetAddress::from(SocketAddr::V6(mapped));
+
+    assert!(address == ipv4_loopback(7777));
+    assert_eq!(address.scopeID, 0);
+    assert_eq!(
+        address.parse_any(),
+        SocketAddr::from((Ipv4Addr::LOCALHOST, 7777))
+    );
+}
+
+#[test]
+fn ipv4_client_reaches_a_dual_mode_host() {
+    let mut server = enet_host_create(
+        &ipv6_any(),
+        1,
+        1,
+        0,
+        0,
+        ENetHostOption::ENET_HOSTOPT_IPV6_DUALMODE,
+    )
+    .unwrap();
+    let mut client = enet_host_create(
+        &ipv4_loopback(0),
+        1,
+        1,
+        0,
+        0,
+        ENetHostOption::ENET_HOSTOPT_IPV4,
+    )
+    .unwrap();
+
+    let serverAddress = ipv4_loopback(server.address.port);
+    enet_host_connect(&mut client, &serverAddress, 1, 0).unwrap();
+
+    let mut serverPeer = None;
+    for _ in 0..200 {
+        let mut event = ENetEvent::default();
+        enet_host_service(&mut client, Some(&mut event), 5).unwrap();
+
+        let mut event = ENetEvent::default();
+        if enet_host_service(&mut server, Some(&mut event), 5).unwrap() == 1 {
+            if let ENetEvent::CONNECT { peer, .. } = event {
+                serverPeer = Some(peer);
+                break;
+            }
+        }
+    }
+
+    let peer = serverPeer.expect("no connect event on the dual-mode host");
+    assert!(server.peers[peer as usize].address == ipv4_loopback(client.address.port));
+}
@@ -0,0 +1,99 @@
+#![allow(non_snake_case)]
+
+//! Failure paths of the public API report an `ENetError` instead of
+//! panicking or returning a bare status code.
+
+use enet_rust::c_host::*;
+use enet_rust::c_packet::enet_packet_create;
+use enet_rust::c_peer::enet_peer_send;
+use enet_rust::c_win32::{enet_address_get_host_ip, enet_socket_send};
+use enet_rust::h_enet::*;
+use enet_rust::h_protocol::ENET_PROTOCOL_MAXIMUM_PEER_ID;
+use enet_rust::h_win32::ENetBuffer;
+use std::cell::RefCell;
+use std::net::SocketAddr;
+use std::rc::Rc;
+
+fn create_host(peerCount: usize) -> Result<ENetHost, ENetError> {
+    let address = ENetAddress::from(SocketAddr::from(([127, 0, 0, 1], 0)));
+
+    enet_host_create(
+        &address,
+        peerCount,
+        1,
+        0,
+        0,
+        ENetHostOption::ENET_HOSTOPT_IPV4,
+    )
+}
+
+fn ipv6_address() -> ENetAddress {
+    ENetAddress::from("[::1]:7777".parse::<SocketAddr>().unwrap())
+}
+
+#[test]
+fn host_create_rejects_too_many_peers() {
+    assert!(matches!(
+        create_host(ENET_PROTOCOL_MAXIMUM_PEER_ID as usize + 1),
+        Err(ENetError::ENET_ERROR_PEER_LIMIT)
+    ));
+}
+
+#[test]
+fn ipv4_host_rejects_ipv6_addresses() {
+    let mut host = create_host(1).unwrap();
+    let address = ipv6_address();
+
+    let data: [&[u8]; 1] = [&[0u8]];
+    let buffer = ENetBuffer {
+        dataID: 0,
+        dataLength: 1,
+    };
+
+    assert!(matches!(
+        enet_socket_send(&*host.socket, &address, &data, &[buffer], 1),
+        Err(ENetError::ENET_ERROR_ADDRESS_FAMILY)
+    ));
+    assert!(matches!(
+        enet_host_ping(&host, &address),
+        Err(ENetError::ENET_ERROR_ADDRESS_FAMILY)
+    ));
+    assert!(matches!(
+        enet_host_connect(&mut host, &address, 1, 0),
+        Err(ENetError::ENET_ERROR_ADDRESS_FAMILY)
+    ));
+}
+
+#[test]
+fn host_connect_reports_peer_limit() {
+    let mut host = create_host(1).unwrap();
+    let address = ENetAddress::from(host.socket.local_addr().unwrap());
+
+    assert_eq!(enet_host_connect(&mut host, &address, 1, 0).unwrap(), 0);
+    assert!(matches!(
+        enet_host_connect(&mut host, &address, 1, 0),
+        Err(ENetError::ENET_ERROR_PEER_LIMIT)
+    ));
+}
+
+#[test]
+fn peer_send_requires_a_connected_peer() {
+    let mut host = create_host(1).unwrap();
+    let packet = enet_packet_create(Rc::new(RefCell::new(vec![0; 4])), 4, 0);
+
+    assert!(matches!(
+        enet_peer_send(&mut host, 0, 0, packet),
+        Err(ENetError::ENET_ERROR_NOT_CONNECTED)
+    ));
+}
+
+#[test]
+fn address_get_host_ip_reports_short_buffers() {
+    let address = ipv6_address();
+    let mut ip = [0u8; 2];
+
+    assert!(matches!(
+        enet_address_get_host_ip(&address, &mut ip),
+        Err(ENetError::ENET_ERROR_BUFFER_TOO_SMALL)
+    ));
+}
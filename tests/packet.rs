@@ -0,0 +1,155 @@
+#![allow(non_snake_case)]
+
+//! Outgoing packets are reference counted across the commands that carry
+//! them, and the free callback runs once the last of those is released.
+
+use enet_rust::c_host::*;
+use enet_rust::c_packet::enet_packet_create;
+use enet_rust::c_peer::{enet_peer_reset, enet_peer_send};
+use enet_rust::c_sansio::*;
+use enet_rust::h_enet::ENetPacketFlag::*;
+use enet_rust::h_enet::*;
+use enet_rust::h_sansio::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+thread_local! {
+    static FREED: RefCell<Vec<(u32, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+fn freed(packet: ENetPacket) {
+    FREED.with(|freed| freed.borrow_mut().push((packet.flags, packet.dataLength)));
+}
+
+fn take_freed() -> Vec<(u32, usize)> {
+    FREED.with(|freed| freed.take())
+}
+
+fn service(sansIO: &ENetSansIO, host: &mut ENetHost, time: u32) {
+    let mut event = ENetEvent::default();
+    while enet_sans_io_service(sansIO, host, time, Some(&mut event)).unwrap() > 0 {}
+}
+
+fn exchange(sansIOs: &[ENetSansIO; 2], hosts: &mut [ENetHost], from: u32, to: u32) {
+    for time in (from..to).step_by(10) {
+        for i in 0..2 {
+            service(&sansIOs[i], &mut hosts[i], time);
+
+            while let Some(datagram) = enet_sans_io_poll_datagram(&sansIOs[i]) {
+                enet_sans_io_receive(
+                    &sansIOs[1 - i],
+                    time + 10,
+                    sansIOs[i].address,
+                    &datagram.data,
+                );
+            }
+        }
+    }
+}
+
+fn connect() -> ([ENetSansIO; 2], Vec<ENetHost>, u16) {
+    let sansIOs = [
+        enet_sans_io_create("10.0.0.1:7777".parse().unwrap(), 0),
+        enet_sans_io_create("10.0.0.2:7777".parse().unwrap(), 0),
+    ];
+    let mut hosts: Vec<ENetHost> = sansIOs
+        .iter()
+        .map(|sansIO| enet_host_create_sans_io(sansIO, 1, 1, 0, 0).unwrap())
+        .collect();
+
+    let serverAddress = hosts[0].address;
+    let peer = enet_host_connect(&mut hosts[1], &serverAddress, 1, 0).unwrap();
+    exchange(&sansIOs, &mut hosts, 0, 80);
+    take_freed();
+
+    (sansIOs, hosts, peer)
+}
+
+fn packet(length: usize, flags: u32) -> ENetPacket {
+    let mut packet = enet_packet_create(Rc::new(RefCell::new(vec![7; length])), length, flags);
+    packet.freeCallback = Some(freed);
+    packet
+}
+
+fn queued(host: &ENetHost, peer: u16) -> Vec<Rc<RefCell<ENetPacket>>> {
+    let peer = &host.peers[peer as usize];
+    peer.outgoingCommands
+        .iter()
+        .chain(peer.outgoingSendReliableCommands.iter())
+        .filter_map(|outgoingCommand| outgoingCommand.packet.clone())
+        .collect()
+}
+
+#[test]
+fn fragments_share_one_packet_freed_once_all_are_acknowledged() {
+    let (sansIOs, mut hosts, peer) = connect();
+
+    enet_peer_send(
+        &mut hosts[1],
+        peer,
+        0,
+        packet(4000, ENET_PACKET_FLAG_RELIABLE as u32),
+    )
+    .unwrap();
+
+    let fragments = queued(&hosts[1], peer);
+    assert_eq!(fragments.len(), 3);
+    assert!(fragments
+        .iter()
+        .all(|fragment| Rc::ptr_eq(fragment, &fragments[0])));
+    assert_eq!(fragments[0].borrow().referenceCount, 3);
+    drop(fragments);
+
+    service(&sansIOs[1], &mut hosts[1], 100);
+    assert!(take_freed().is_empty());
+
+    exchange(&sansIOs, &mut hosts, 100, 200);
+    assert_eq!(
+        take_freed(),
+        [(
+            ENET_PACKET_FLAG_RELIABLE as u32 | ENET_PACKET_FLAG_SENT as u32,
+            4000
+        )]
+    );
+}
+
+#[test]
+fn unreliable_packets_are_freed_as_sent_after_the_flush() {
+    let (sansIOs, mut hosts, peer) = connect();
+
+    enet_peer_send(&mut hosts[1], peer, 0, packet(100, 0)).unwrap();
+    assert_eq!(queued(&hosts[1], peer)[0].borrow().referenceCount, 1);
+
+    service(&sansIOs[1], &mut hosts[1], 100);
+    assert_eq!(take_freed(), [(ENET_PACKET_FLAG_SENT as u32, 100)]);
+}
+
+#[test]
+fn packets_dropped_by_a_reset_are_freed_without_the_sent_flag() {
+    let (_sansIOs, mut hosts, peer) = connect();
+
+    enet_peer_send(
+        &mut hosts[1],
+        peer,
+        0,
+        packet(100, ENET_PACKET_FLAG_RELIABLE as u32),
+    )
+    .unwrap();
+    enet_peer_send(&mut hosts[1], peer, 0, packet(200, 0)).unwrap();
+
+    enet_peer_reset(&mut hosts[1], peer);
+    assert_eq!(
+        take_freed(),
+        [(0, 200), (ENET_PACKET_FLAG_RELIABLE as u32, 100)]
+    );
+
+    enet_peer_send(
+        &mut hosts[0],
+        0,
+        0,
+        packet(300, ENET_PACKET_FLAG_RELIABLE as u32),
+    )
+    .unwrap();
+    enet_host_destroy(hosts.remove(0));
+    assert_eq!(take_freed(), [(ENET_PACKET_FLAG_RELIABLE as u32, 300)]);
+}
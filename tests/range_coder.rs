@@ -0,0 +1,159 @@
+#![allow(non_snake_case)]
+
+//! Checks `enet_range_coder_compress` and `enet_range_coder_decompress`
+//! against the vectors in `tests/range_coder/vectors.txt`. Each vector is a
+//! list of directives, one per line:
+//!
+//! - `vector <name>` starts a vector.
+//! - `buffer <hex>` appends an input buffer.
+//! - `buffer repeat <hex> <count>` appends a buffer of `count` copies of the
+//!   bytes.
+//! - `buffer random <seed> <length> <alphabet>` appends `length` bytes drawn
+//!   from the ASCII `alphabet` by the C library's sample `rand` generator,
+//!   seeded with `seed`.
+//! - `compressed <hex>` appends to the expected range coder output.
+//!
+//! `tests/range_coder/vectors.c` reads the same file and prints it back with
+//! the `compressed` lines produced by ENet's `compress.c`.
+
+use enet_rust::c_compress::*;
+use enet_rust::h_win32::ENetBuffer;
+use std::any::Any;
+
+struct Vector {
+    name: String,
+    buffers: Vec<Vec<u8>>,
+    compressed: Vec<u8>,
+}
+
+fn parse_hex(text: &str) -> Vec<u8> {
+    assert!(text.len().is_multiple_of(2), "odd hex literal {}", text);
+
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).expect("hex literal"))
+        .collect()
+}
+
+fn random(seed: u32, length: usize, alphabet: &[u8]) -> Vec<u8> {
+    let mut state = seed;
+
+    (0..length)
+        .map(|_| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            alphabet[((state >> 16) & 0x7FFF) as usize % alphabet.len()]
+        })
+        .collect()
+}
+
+fn parse_vectors(text: &str) -> Vec<Vector> {
+    let mut vectors: Vec<Vector> = Vec::new();
+
+    for line in text.lines() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.is_empty() || words[0].starts_with('#') {
+            continue;
+        }
+
+        if words[0] == "vector" {
+            vectors.push(Vector {
+                name: words[1].to_string(),
+                buffers: Vec::new(),
+                compressed: Vec::new(),
+            });
+            continue;
+        }
+
+        let vector = vectors.last_mut().expect("directive outside a vector");
+        match words.as_slice() {
+            ["buffer", "repeat", bytes, count] => {
+                vector
+                    .buffers
+                    .push(parse_hex(bytes).repeat(count.parse().unwrap()));
+            }
+            ["buffer", "random", seed, length, alphabet] => {
+                vector.buffers.push(random(
+                    seed.parse().unwrap(),
+                    length.parse().unwrap(),
+                    alphabet.as_bytes(),
+                ));
+            }
+            ["buffer", bytes] => vector.buffers.push(parse_hex(bytes)),
+            ["compressed", bytes] => vector.compressed.extend(parse_hex(bytes)),
+            _ => panic!("unknown directive {}", line),
+        }
+    }
+
+    vectors
+}
+
+fn compress(buffers: &[Vec<u8>]) -> Vec<u8> {
+    let mut rangeCoder: Box<dyn Any> = Box::new(enet_range_coder_create());
+    let data: Vec<&[u8]> = buffers.iter().map(|buffer| &buffer[..]).collect();
+    let inBuffers: Vec<ENetBuffer> = buffers
+        .iter()
+        .enumerate()
+        .map(|(dataID, buffer)| ENetBuffer {
+            dataID,
+            dataLength: buffer.len(),
+        })
+        .collect();
+    let inLimit = buffers.iter().map(Vec::len).sum();
+
+    let mut outData = vec![0; inLimit * 2 + 16];
+    let outLimit = outData.len();
+    let outLength = enet_range_coder_compress(
+        Some(&mut rangeCoder),
+        &data,
+        &inBuffers,
+        inBuffers.len(),
+        inLimit,
+        &mut outData,
+        outLimit,
+    );
+
+    outData.truncate(outLength);
+    outData
+}
+
+fn decompress(inData: &[u8], outLimit: usize) -> Vec<u8> {
+    let mut rangeCoder: Box<dyn Any> = Box::new(enet_range_coder_create());
+
+    let mut outData = vec![0; outLimit];
+    let outLength = enet_range_coder_decompress(
+        Some(&mut rangeCoder),
+        inData,
+        inData.len(),
+        &mut outData,
+        outLimit,
+    );
+
+    outData.truncate(outLength);
+    outData
+}
+
+#[test]
+fn range_coder_vectors() {
+    let vectors = parse_vectors(include_str!("range_coder/vectors.txt"));
+    assert!(!vectors.is_empty());
+
+    for vector in vectors {
+        let compressed = compress(&vector.buffers);
+        assert!(
+            compressed == vector.compressed,
+            "{}: compressed to {}",
+            vector.name,
+            compressed
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>()
+        );
+
+        let original = vector.buffers.concat();
+        assert!(
+            decompress(&vector.compressed, original.len()) == original,
+            "{}: decompressed output differs",
+            vector.name
+        );
+    }
+}
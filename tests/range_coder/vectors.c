@@ -0,0 +1,145 @@
+/* Prints vectors.txt back with the compressed lines produced by ENet's
+ * compress.c, so the vectors can be checked against or regenerated from
+ * C ENet:
+ *
+ *     cc vectors.c -o vectors -lenet
+ *     ./vectors < vectors.txt | diff vectors.txt -
+ */
+
+#define _POSIX_C_SOURCE 200809L
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <enet/enet.h>
+
+#define MAXIMUM_BUFFERS 16
+
+static ENetBuffer buffers [MAXIMUM_BUFFERS];
+static size_t bufferCount = 0;
+
+static enet_uint8 *
+parse_hex (const char * text, size_t * length)
+{
+    size_t i;
+    enet_uint8 * data;
+
+    * length = strlen (text) / 2;
+    data = malloc (* length);
+    for (i = 0; i < * length; ++ i)
+      sscanf (& text [i * 2], "%2hhx", & data [i]);
+    return data;
+}
+
+static enet_uint8 *
+random_bytes (unsigned long seed, size_t length, const char * alphabet)
+{
+    size_t i, alphabetLength = strlen (alphabet);
+    enet_uint32 state = (enet_uint32) seed;
+    enet_uint8 * data = malloc (length);
+
+    for (i = 0; i < length; ++ i)
+    {
+       state = state * 1103515245 + 12345;
+       data [i] = alphabet [((state >> 16) & 0x7FFF) % alphabetLength];
+    }
+    return data;
+}
+
+static void
+add_buffer (char * arguments)
+{
+    char * words [4], * word;
+    size_t wordCount = 0, length, count, i;
+    enet_uint8 * data, * bytes;
+
+    for (word = strtok (arguments, " \t"); word != NULL && wordCount < 4; word = strtok (NULL, " \t"))
+      words [wordCount ++] = word;
+
+    if (wordCount == 3 && strcmp (words [0], "repeat") == 0)
+    {
+       bytes = parse_hex (words [1], & length);
+       count = strtoul (words [2], NULL, 10);
+       data = malloc (length * count);
+       for (i = 0; i < count; ++ i)
+         memcpy (& data [i * length], bytes, length);
+       free (bytes);
+       length *= count;
+    }
+    else
+    if (wordCount == 4 && strcmp (words [0], "random") == 0)
+    {
+       length = strtoul (words [2], NULL, 10);
+       data = random_bytes (strtoul (words [1], NULL, 10), length, words [3]);
+    }
+    else
+      data = parse_hex (words [0], & length);
+
+    buffers [bufferCount].data = data;
+    buffers [bufferCount].dataLength = length;
+    ++ bufferCount;
+}
+
+static void
+flush_vector (void)
+{
+    size_t i, inLimit = 0, outLimit, outLength;
+    enet_uint8 * outData;
+    void * rangeCoder;
+
+    if (bufferCount == 0)
+      return;
+
+    for (i = 0; i < bufferCount; ++ i)
+      inLimit += buffers [i].dataLength;
+
+    outLimit = inLimit * 2 + 16;
+    outData = malloc (outLimit);
+    rangeCoder = enet_range_coder_create ();
+    outLength = enet_range_coder_compress (rangeCoder, buffers, bufferCount, inLimit, outData, outLimit);
+    enet_range_coder_destroy (rangeCoder);
+
+    for (i = 0; i < outLength; ++ i)
+    {
+       if (i % 32 == 0)
+         printf ("compressed ");
+       printf ("%02x", outData [i]);
+       if (i % 32 == 31 || i + 1 == outLength)
+         printf ("\n");
+    }
+
+    free (outData);
+    for (i = 0; i < bufferCount; ++ i)
+      free (buffers [i].data);
+    bufferCount = 0;
+}
+
+int
+main (void)
+{
+    char * line = NULL;
+    size_t capacity = 0;
+    ssize_t length;
+
+    while ((length = getline (& line, & capacity, stdin)) >= 0)
+    {
+       if (length > 0 && line [length - 1] == '\n')
+         line [-- length] = '\0';
+
+       if (strncmp (line, "buffer ", 7) == 0)
+       {
+          printf ("%s\n", line);
+          add_buffer (& line [7]);
+          continue;
+       }
+
+       flush_vector ();
+
+       if (strncmp (line, "compressed ", 11) != 0)
+         printf ("%s\n", line);
+    }
+
+    flush_vector ();
+    free (line);
+    return 0;
+}
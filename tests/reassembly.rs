@@ -0,0 +1,120 @@
+#![allow(non_snake_case)]
+
+//! Fragment reassembly and in-order dispatch of reliable packets whose
+//! datagrams the test delivers out of order.
+
+use enet_rust::c_host::*;
+use enet_rust::c_packet::enet_packet_create;
+use enet_rust::c_peer::enet_peer_send;
+use enet_rust::c_sansio::*;
+use enet_rust::h_enet::ENetPacketFlag::*;
+use enet_rust::h_enet::*;
+use enet_rust::h_sansio::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn service(sansIO: &ENetSansIO, host: &mut ENetHost, time: u32) -> Vec<Vec<u8>> {
+    let mut received = Vec::new();
+
+    loop {
+        let mut event = ENetEvent::default();
+        if enet_sans_io_service(sansIO, host, time, Some(&mut event)).unwrap() == 0 {
+            return received;
+        }
+
+        if let ENetEvent::RECEIVE { packet, .. } = event {
+            received.push(packet.data.as_ref().unwrap().borrow()[..].to_vec());
+        }
+    }
+}
+
+fn connect() -> ([ENetSansIO; 2], Vec<ENetHost>, u16) {
+    let sansIOs = [
+        enet_sans_io_create("10.0.0.1:7777".parse().unwrap(), 0),
+        enet_sans_io_create("10.0.0.2:7777".parse().unwrap(), 0),
+    ];
+    let mut hosts: Vec<ENetHost> = sansIOs
+        .iter()
+        .map(|sansIO| enet_host_create_sans_io(sansIO, 1, 1, 0, 0).unwrap())
+        .collect();
+
+    let serverAddress = hosts[0].address;
+    let peer = enet_host_connect(&mut hosts[1], &serverAddress, 1, 0).unwrap();
+
+    for time in (0..80).step_by(10) {
+        for i in 0..2 {
+            service(&sansIOs[i], &mut hosts[i], time);
+
+            while let Some(datagram) = enet_sans_io_poll_datagram(&sansIOs[i]) {
+                enet_sans_io_receive(
+                    &sansIOs[1 - i],
+                    time + 10,
+                    sansIOs[i].address,
+                    &datagram.data,
+                );
+            }
+        }
+    }
+
+    (sansIOs, hosts, peer)
+}
+
+fn send(host: &mut ENetHost, peer: u16, data: &[u8]) {
+    let packet = enet_packet_create(
+        Rc::new(RefCell::new(data.to_vec())),
+        data.len(),
+        ENET_PACKET_FLAG_RELIABLE as u32,
+    );
+    enet_peer_send(host, peer, 0, packet).unwrap();
+}
+
+fn datagrams(sansIO: &ENetSansIO) -> Vec<Vec<u8>> {
+    std::iter::from_fn(|| enet_sans_io_poll_datagram(sansIO))
+        .map(|datagram| datagram.data)
+        .collect()
+}
+
+#[test]
+fn fragments_arriving_in_reverse_are_reassembled_once_complete() {
+    let (sansIOs, mut hosts, peer) = connect();
+    let time = 100;
+
+    let data: Vec<u8> = (0..4000).map(|i| (i % 251) as u8).collect();
+    send(&mut hosts[1], peer, &data);
+    service(&sansIOs[1], &mut hosts[1], time);
+
+    let fragments = datagrams(&sansIOs[1]);
+    assert_eq!(fragments.len(), 3);
+
+    for fragment in fragments[1..].iter().rev() {
+        enet_sans_io_receive(&sansIOs[0], time, sansIOs[1].address, fragment);
+        assert!(service(&sansIOs[0], &mut hosts[0], time).is_empty());
+    }
+
+    enet_sans_io_receive(&sansIOs[0], time, sansIOs[1].address, &fragments[0]);
+    assert_eq!(service(&sansIOs[0], &mut hosts[0], time), [data]);
+}
+
+#[test]
+fn reliable_packets_are_dispatched_in_sequence_order() {
+    let (sansIOs, mut hosts, peer) = connect();
+    let time = 100;
+
+    let mut sent = Vec::new();
+    for data in [b"first", b"secnd", b"third"] {
+        send(&mut hosts[1], peer, data);
+        service(&sansIOs[1], &mut hosts[1], time);
+        sent.extend(datagrams(&sansIOs[1]));
+    }
+    assert_eq!(sent.len(), 3);
+
+    let receive = |hosts: &mut Vec<ENetHost>, datagram: &[u8]| {
+        enet_sans_io_receive(&sansIOs[0], time, sansIOs[1].address, datagram);
+        service(&sansIOs[0], &mut hosts[0], time)
+    };
+
+    assert!(receive(&mut hosts, &sent[2]).is_empty());
+    assert_eq!(receive(&mut hosts, &sent[0]), [b"first"]);
+    assert_eq!(receive(&mut hosts, &sent[1]), [b"secnd", b"third"]);
+    assert!(receive(&mut hosts, &sent[2]).is_empty());
+}
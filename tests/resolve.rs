@@ -0,0 +1,105 @@
+#![allow(non_snake_case)]
+
+//! Forward and reverse name resolution. The names used here come from
+//! /etc/hosts, so no DNS server is needed.
+
+use enet_rust::c_win32::*;
+use enet_rust::h_enet::*;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+
+fn name(host: &[u8]) -> &str {
+    let length = host
+        .iter()
+        .position(|&b| b == 0)
+        .expect("no NUL terminator");
+    std::str::from_utf8(&host[..length]).unwrap()
+}
+
+#[test]
+fn set_host_ip_parses_both_families_and_keeps_the_port() {
+    let mut address = ENetAddress::new();
+    address.port = 7777;
+
+    enet_address_set_host_ip(&mut address, "192.0.2.1").unwrap();
+    assert_eq!(
+        address.parse_any(),
+        SocketAddr::from((Ipv4Addr::new(192, 0, 2, 1), 7777))
+    );
+
+    enet_address_set_host_ip(&mut address, "2001:db8::1").unwrap();
+    assert_eq!(
+        address.parse_any(),
+        SocketAddr::from(("2001:db8::1".parse::<Ipv6Addr>().unwrap(), 7777))
+    );
+
+    assert!(matches!(
+        enet_address_set_host_ip(&mut address, "localhost"),
+        Err(ENetError::ENET_ERROR_INVALID_ADDRESS)
+    ));
+}
+
+#[test]
+fn set_host_resolves_names_for_the_host_family() {
+    let mut address = ENetAddress::new();
+    address.port = 7777;
+
+    enet_address_set_host(&mut address, "localhost", ENetHostOption::ENET_HOSTOPT_IPV4).unwrap();
+    assert_eq!(
+        address.parse_any(),
+        SocketAddr::from((Ipv4Addr::LOCALHOST, 7777))
+    );
+
+    let mut address = ENetAddress::new();
+    enet_address_set_host(
+        &mut address,
+        "127.0.0.1",
+        ENetHostOption::ENET_HOSTOPT_IPV6_DUALMODE,
+    )
+    .unwrap();
+    assert!(address.is_ipv4());
+
+    assert!(matches!(
+        enet_address_set_host(&mut address, "::1", ENetHostOption::ENET_HOSTOPT_IPV4),
+        Err(ENetError::ENET_ERROR_ADDRESS_FAMILY)
+    ));
+}
+
+#[test]
+fn get_host_looks_up_the_name() {
+    let mut address = ENetAddress::new();
+    enet_address_set_host_ip(&mut address, "127.0.0.1").unwrap();
+
+    let mut hostName = [0xFFu8; 256];
+    enet_address_get_host(&address, &mut hostName).unwrap();
+    assert_eq!(name(&hostName), "localhost");
+
+    let mut hostIP = [0xFFu8; 256];
+    enet_address_get_host_ip(&address, &mut hostIP).unwrap();
+    assert_eq!(name(&hostIP), "127.0.0.1");
+}
+
+#[test]
+fn get_host_falls_back_to_the_numeric_form() {
+    let mut address = ENetAddress::new();
+    enet_address_set_host_ip(&mut address, "192.0.2.1").unwrap();
+
+    let mut hostName = [0xFFu8; 256];
+    enet_address_get_host(&address, &mut hostName).unwrap();
+    assert_eq!(name(&hostName), "192.0.2.1");
+}
+
+#[test]
+fn get_host_ip_needs_room_for_the_terminator() {
+    let mut address = ENetAddress::new();
+    enet_address_set_host_ip(&mut address, "192.0.2.1").unwrap();
+
+    let mut exact = [0xFFu8; 9];
+    assert!(matches!(
+        enet_address_get_host_ip(&address, &mut exact),
+        Err(ENetError::ENET_ERROR_BUFFER_TOO_SMALL)
+    ));
+
+    let mut hostIP = [0xFFu8; 10];
+    enet_address_get_host_ip(&address, &mut hostIP).unwrap();
+    assert_eq!(&hostIP, b"192.0.2.1\0");
+}
@@ -0,0 +1,190 @@
+#![allow(non_snake_case)]
+
+//! Retransmission of lost reliable commands with a doubling timeout, and
+//! the disconnect once a peer stops acknowledging.
+
+use enet_rust::c_host::*;
+use enet_rust::c_packet::enet_packet_create;
+use enet_rust::c_peer::{enet_peer_send, enet_peer_timeout};
+use enet_rust::c_protocol::enet_protocol_decode_header;
+use enet_rust::c_sansio::*;
+use enet_rust::h_enet::ENetPacketFlag::*;
+use enet_rust::h_enet::*;
+use enet_rust::h_sansio::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn service(sansIO: &ENetSansIO, host: &mut ENetHost, time: u32) -> Vec<ENetEvent> {
+    let mut events = Vec::new();
+
+    loop {
+        let mut event = ENetEvent::default();
+        if enet_sans_io_service(sansIO, host, time, Some(&mut event)).unwrap() == 0 {
+            return events;
+        }
+
+        events.push(event);
+    }
+}
+
+fn connect(duration: u32) -> ([ENetSansIO; 2], Vec<ENetHost>, u16) {
+    let sansIOs = [
+        enet_sans_io_create("10.0.0.1:7777".parse().unwrap(), 0),
+        enet_sans_io_create("10.0.0.2:7777".parse().unwrap(), 0),
+    ];
+    let mut hosts: Vec<ENetHost> = sansIOs
+        .iter()
+        .map(|sansIO| enet_host_create_sans_io(sansIO, 1, 1, 0, 0).unwrap())
+        .collect();
+
+    let serverAddress = hosts[0].address;
+    let peer = enet_host_connect(&mut hosts[1], &serverAddress, 1, 0).unwrap();
+
+    for time in (0..duration).step_by(10) {
+        for i in 0..2 {
+            service(&sansIOs[i], &mut hosts[i], time);
+
+            while let Some(datagram) = enet_sans_io_poll_datagram(&sansIOs[i]) {
+                enet_sans_io_receive(
+                    &sansIOs[1 - i],
+                    time + 10,
+                    sansIOs[i].address,
+                    &datagram.data,
+                );
+            }
+        }
+    }
+
+    (sansIOs, hosts, peer)
+}
+
+fn send(host: &mut ENetHost, peer: u16) {
+    let packet = enet_packet_create(
+        Rc::new(RefCell::new(b"lost".to_vec())),
+        4,
+        ENET_PACKET_FLAG_RELIABLE as u32,
+    );
+    enet_peer_send(host, peer, 0, packet).unwrap();
+}
+
+fn commands(sansIO: &ENetSansIO) -> Option<Vec<u8>> {
+    let datagram = enet_sans_io_poll_datagram(sansIO)?;
+    assert!(enet_sans_io_poll_datagram(sansIO).is_none());
+
+    let (_, offset) = enet_protocol_decode_header(&datagram.data).unwrap();
+    Some(datagram.data[offset..].to_vec())
+}
+
+#[test]
+fn lost_reliable_commands_are_resent_with_a_doubling_timeout() {
+    let (sansIOs, mut hosts, peer) = connect(ENET_HOST_BANDWIDTH_THROTTLE_INTERVAL + 200);
+    let mut time = ENET_HOST_BANDWIDTH_THROTTLE_INTERVAL + 200;
+
+    send(&mut hosts[1], peer);
+    service(&sansIOs[1], &mut hosts[1], time);
+    let original = commands(&sansIOs[1]).unwrap();
+
+    let mut roundTripTimeout =
+        hosts[1].peers[peer as usize].sentReliableCommands[0].roundTripTimeout;
+    assert!(roundTripTimeout > 0);
+
+    for attempt in 2..5 {
+        service(&sansIOs[1], &mut hosts[1], time + roundTripTimeout - 1);
+        assert!(commands(&sansIOs[1]).is_none());
+
+        time += roundTripTimeout;
+        service(&sansIOs[1], &mut hosts[1], time);
+        assert_eq!(commands(&sansIOs[1]).unwrap(), original);
+
+        let clientPeer = &hosts[1].peers[peer as usize];
+        let outgoingCommand = &clientPeer.sentReliableCommands[0];
+        assert_eq!(outgoingCommand.sendAttempts, attempt);
+        assert_eq!(outgoingCommand.roundTripTimeout, roundTripTimeout * 2);
+        assert_eq!(clientPeer.packetsLost, attempt as u32 - 1);
+
+        roundTripTimeout *= 2;
+    }
+
+    time += roundTripTimeout;
+    service(&sansIOs[1], &mut hosts[1], time);
+    let datagram = enet_sans_io_poll_datagram(&sansIOs[1]).unwrap();
+    enet_sans_io_receive(&sansIOs[0], time, sansIOs[1].address, &datagram.data);
+
+    match service(&sansIOs[0], &mut hosts[0], time).as_slice() {
+        [ENetEvent::RECEIVE { packet, .. }] => {
+            assert_eq!(&packet.data.as_ref().unwrap().borrow()[..], b"lost");
+        }
+        _ => panic!("expected the retransmitted packet"),
+    }
+
+    while let Some(datagram) = enet_sans_io_poll_datagram(&sansIOs[0]) {
+        enet_sans_io_receive(&sansIOs[1], time, sansIOs[0].address, &datagram.data);
+    }
+    service(&sansIOs[1], &mut hosts[1], time);
+    assert!(hosts[1].peers[peer as usize]
+        .sentReliableCommands
+        .is_empty());
+}
+
+#[test]
+fn unacknowledged_peers_disconnect_once_the_timeout_limit_is_reached() {
+    let (sansIOs, mut hosts, peer) = connect(80);
+    let start = 100;
+
+    send(&mut hosts[1], peer);
+    service(&sansIOs[1], &mut hosts[1], start);
+    let roundTripTimeout = hosts[1].peers[peer as usize].sentReliableCommands[0].roundTripTimeout;
+
+    let mut time = start;
+    let mut events = Vec::new();
+    while events.is_empty() {
+        time += 10;
+        events = service(&sansIOs[1], &mut hosts[1], time);
+        while enet_sans_io_poll_datagram(&sansIOs[1]).is_some() {}
+
+        assert!(time - start <= 2 * ENET_PEER_TIMEOUT_MAXIMUM);
+    }
+
+    match events.as_slice() {
+        [ENetEvent::DISCONNECT {
+            peer: eventPeer, ..
+        }] => assert_eq!(*eventPeer, peer),
+        _ => panic!("expected a disconnect event"),
+    }
+
+    assert!(time - start >= ENET_PEER_TIMEOUT_MINIMUM);
+    assert_eq!(
+        time - start,
+        roundTripTimeout * (2 * ENET_PEER_TIMEOUT_LIMIT - 1)
+    );
+    assert!(hosts[1].peers[peer as usize].state == ENetPeerState::ENET_PEER_STATE_DISCONNECTED);
+}
+
+#[test]
+fn backoff_saturates_instead_of_overflowing() {
+    let (sansIOs, mut hosts, peer) = connect(80);
+
+    enet_peer_timeout(&mut hosts[1], peer, u32::MAX, u32::MAX, u32::MAX);
+    hosts[1].peers[peer as usize].roundTripTime = 512;
+    hosts[1].peers[peer as usize].roundTripTimeVariance = 0;
+
+    send(&mut hosts[1], peer);
+
+    let mut time: u32 = 100;
+    loop {
+        service(&sansIOs[1], &mut hosts[1], time);
+        while enet_sans_io_poll_datagram(&sansIOs[1]).is_some() {}
+
+        let roundTripTimeout =
+            hosts[1].peers[peer as usize].sentReliableCommands[0].roundTripTimeout;
+        if roundTripTimeout == u32::MAX {
+            break;
+        }
+
+        time = time.wrapping_add(roundTripTimeout);
+    }
+
+    let clientPeer = &hosts[1].peers[peer as usize];
+    assert!(clientPeer.state == ENetPeerState::ENET_PEER_STATE_CONNECTED);
+    assert_eq!(clientPeer.sentReliableCommands[0].sendAttempts, 24);
+}
@@ -0,0 +1,199 @@
+#![allow(non_snake_case)]
+
+//! Hosts driven through the sans-IO core, with the test moving datagrams
+//! and picking the time of every service call.
+
+use enet_rust::c_host::*;
+use enet_rust::c_packet::enet_packet_create;
+use enet_rust::c_peer::enet_peer_send;
+use enet_rust::c_sansio::*;
+use enet_rust::h_enet::ENetPacketFlag::*;
+use enet_rust::h_enet::*;
+use enet_rust::h_sansio::*;
+use enet_rust::h_time::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn service(sansIO: &ENetSansIO, host: &mut ENetHost, time: u32) -> Vec<ENetEvent> {
+    let mut events = Vec::new();
+
+    loop {
+        let mut event = ENetEvent::default();
+        if enet_sans_io_service(sansIO, host, time, Some(&mut event)).unwrap() == 0 {
+            return events;
+        }
+
+        events.push(event);
+    }
+}
+
+fn earliest(deadlines: &[Option<u32>]) -> Option<u32> {
+    deadlines
+        .iter()
+        .flatten()
+        .copied()
+        .reduce(|a, b| if ENET_TIME_LESS(a, b) { a } else { b })
+}
+
+#[test]
+fn hosts_connect_and_exchange_packets_through_datagram_queues() {
+    let sansIOs = [
+        enet_sans_io_create("10.0.0.1:7777".parse().unwrap(), 0),
+        enet_sans_io_create("10.0.0.2:7777".parse().unwrap(), 0),
+    ];
+    let mut hosts: Vec<ENetHost> = sansIOs
+        .iter()
+        .map(|sansIO| enet_host_create_sans_io(sansIO, 1, 1, 0, 0).unwrap())
+        .collect();
+
+    let serverAddress = hosts[0].address;
+    let peer = enet_host_connect(&mut hosts[1], &serverAddress, 1, 42).unwrap();
+
+    let mut time = 0;
+    let mut events = Vec::new();
+    for _ in 0..8 {
+        for i in 0..2 {
+            events.extend(service(&sansIOs[i], &mut hosts[i], time));
+
+            while let Some(datagram) = enet_sans_io_poll_datagram(&sansIOs[i]) {
+                let target = sansIOs
+                    .iter()
+                    .find(|sansIO| sansIO.address == datagram.address)
+                    .unwrap();
+                enet_sans_io_receive(target, time + 10, sansIOs[i].address, &datagram.data);
+            }
+        }
+
+        time += 10;
+    }
+
+    let connects = events
+        .iter()
+        .filter(|event| matches!(event, ENetEvent::CONNECT { .. }))
+        .count();
+    assert_eq!(connects, 2);
+
+    let packet = enet_packet_create(Rc::new(RefCell::new(b"hello".to_vec())), 5, 1);
+    enet_peer_send(&mut hosts[1], peer, 0, packet).unwrap();
+    service(&sansIOs[1], &mut hosts[1], time);
+
+    let datagram = enet_sans_io_poll_datagram(&sansIOs[1]).unwrap();
+    assert_eq!(datagram.address, sansIOs[0].address);
+    assert!(enet_sans_io_poll_datagram(&sansIOs[1]).is_none());
+
+    enet_sans_io_receive(&sansIOs[0], time, sansIOs[1].address, &datagram.data);
+    match service(&sansIOs[0], &mut hosts[0], time).as_slice() {
+        [ENetEvent::RECEIVE { packet, .. }] => {
+            assert_eq!(&packet.data.as_ref().unwrap().borrow()[..], b"hello");
+        }
+        _ => panic!("expected one receive event"),
+    }
+
+    let acknowledgement = enet_sans_io_poll_datagram(&sansIOs[0]).unwrap();
+    enet_sans_io_receive(&sansIOs[1], time, sansIOs[0].address, &acknowledgement.data);
+    service(&sansIOs[1], &mut hosts[1], time);
+
+    // With nothing in flight, the next work is the ping or the bandwidth
+    // throttle, both of which are scheduled in the future.
+    let deadline = earliest(&[
+        enet_host_next_deadline(&hosts[0]),
+        enet_host_next_deadline(&hosts[1]),
+    ])
+    .unwrap();
+    assert!(ENET_TIME_LESS(time, deadline));
+}
+
+#[test]
+fn deadlines_follow_retransmissions_until_the_peer_times_out() {
+    let sansIO = enet_sans_io_create("10.0.0.2:7777".parse().unwrap(), 0);
+    let mut host = enet_host_create_sans_io(&sansIO, 1, 1, 0, 0).unwrap();
+    assert_eq!(enet_host_next_deadline(&host), None);
+
+    let serverAddress = ENetAddress::from("10.0.0.1:7777".parse::<std::net::SocketAddr>().unwrap());
+    let peer = enet_host_connect(&mut host, &serverAddress, 1, 0).unwrap();
+    assert_eq!(enet_host_next_deadline(&host), Some(0));
+
+    let mut sends = Vec::new();
+    let mut time = 0;
+    loop {
+        let events = service(&sansIO, &mut host, time);
+        while enet_sans_io_poll_datagram(&sansIO).is_some() {
+            sends.push(time);
+        }
+
+        if events
+            .iter()
+            .any(|event| matches!(event, ENetEvent::DISCONNECT { .. }))
+        {
+            break;
+        }
+
+        time = enet_host_next_deadline(&host).unwrap();
+    }
+
+    assert_eq!(sends, [0, 500, 1500, 3500, 7500, 15500]);
+    assert_eq!(time, 31500);
+    assert!(host.peers[peer as usize].state == ENetPeerState::ENET_PEER_STATE_DISCONNECTED);
+    assert_eq!(enet_host_next_deadline(&host), None);
+
+    let packet = enet_packet_create(
+        Rc::new(RefCell::new(vec![0; 4])),
+        4,
+        ENET_PACKET_FLAG_RELIABLE as u32,
+    );
+    assert!(enet_peer_send(&mut host, peer, 0, packet).is_err());
+}
+
+#[test]
+fn packets_sent_while_a_reliable_is_in_flight_are_due_immediately() {
+    let sansIOs = [
+        enet_sans_io_create("10.0.0.1:7777".parse().unwrap(), 0),
+        enet_sans_io_create("10.0.0.2:7777".parse().unwrap(), 0),
+    ];
+    let mut hosts: Vec<ENetHost> = sansIOs
+        .iter()
+        .map(|sansIO| enet_host_create_sans_io(sansIO, 1, 1, 0, 0).unwrap())
+        .collect();
+
+    let serverAddress = hosts[0].address;
+    let peer = enet_host_connect(&mut hosts[1], &serverAddress, 1, 0).unwrap();
+
+    let mut time = 0;
+    for _ in 0..8 {
+        for i in 0..2 {
+            service(&sansIOs[i], &mut hosts[i], time);
+
+            while let Some(datagram) = enet_sans_io_poll_datagram(&sansIOs[i]) {
+                enet_sans_io_receive(&sansIOs[1 - i], time, sansIOs[i].address, &datagram.data);
+            }
+        }
+
+        time += 10;
+    }
+    assert!(hosts[1].peers[peer as usize].state == ENetPeerState::ENET_PEER_STATE_CONNECTED);
+
+    let reliable = || {
+        enet_packet_create(
+            Rc::new(RefCell::new(vec![0; 4])),
+            4,
+            ENET_PACKET_FLAG_RELIABLE as u32,
+        )
+    };
+
+    enet_peer_send(&mut hosts[1], peer, 0, reliable()).unwrap();
+    service(&sansIOs[1], &mut hosts[1], time);
+    assert!(enet_sans_io_poll_datagram(&sansIOs[1]).is_some());
+    assert!(!hosts[1].peers[peer as usize]
+        .sentReliableCommands
+        .is_empty());
+    assert!(ENET_TIME_LESS(
+        time,
+        enet_host_next_deadline(&hosts[1]).unwrap()
+    ));
+
+    enet_peer_send(&mut hosts[1], peer, 0, reliable()).unwrap();
+    assert_eq!(enet_host_next_deadline(&hosts[1]), Some(time));
+
+    service(&sansIOs[1], &mut hosts[1], time);
+    assert!(enet_sans_io_poll_datagram(&sansIOs[1]).is_some());
+}
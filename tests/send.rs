@@ -0,0 +1,166 @@
+#![allow(non_snake_case)]
+
+//! The command `enet_peer_send` queues for each kind of packet, and the
+//! packet that comes out of the receiving host.
+
+use enet_rust::c_host::*;
+use enet_rust::c_packet::enet_packet_create;
+use enet_rust::c_peer::enet_peer_send;
+use enet_rust::c_sansio::*;
+use enet_rust::h_enet::ENetPacketFlag::*;
+use enet_rust::h_enet::*;
+use enet_rust::h_protocol::ENetProtocolCommand::*;
+use enet_rust::h_sansio::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+struct Pair {
+    sansIOs: [ENetSansIO; 2],
+    hosts: Vec<ENetHost>,
+    peer: u16,
+    time: u32,
+}
+
+impl Pair {
+    fn new() -> Pair {
+        let sansIOs = [
+            enet_sans_io_create("10.0.0.1:7777".parse().unwrap(), 0),
+            enet_sans_io_create("10.0.0.2:7777".parse().unwrap(), 0),
+        ];
+        let mut hosts: Vec<ENetHost> = sansIOs
+            .iter()
+            .map(|sansIO| enet_host_create_sans_io(sansIO, 1, 2, 0, 0).unwrap())
+            .collect();
+
+        let serverAddress = hosts[0].address;
+        let peer = enet_host_connect(&mut hosts[1], &serverAddress, 2, 0).unwrap();
+
+        let mut pair = Pair {
+            sansIOs,
+            hosts,
+            peer,
+            time: 0,
+        };
+        pair.run();
+        pair
+    }
+
+    fn run(&mut self) -> Vec<ENetEvent> {
+        let mut events = Vec::new();
+
+        for _ in 0..8 {
+            for i in 0..2 {
+                loop {
+                    let mut event = ENetEvent::default();
+                    if enet_sans_io_service(
+                        &self.sansIOs[i],
+                        &mut self.hosts[i],
+                        self.time,
+                        Some(&mut event),
+                    )
+                    .unwrap()
+                        == 0
+                    {
+                        break;
+                    }
+
+                    events.push(event);
+                }
+
+                while let Some(datagram) = enet_sans_io_poll_datagram(&self.sansIOs[i]) {
+                    enet_sans_io_receive(
+                        &self.sansIOs[1 - i],
+                        self.time + 10,
+                        self.sansIOs[i].address,
+                        &datagram.data,
+                    );
+                }
+            }
+
+            self.time += 10;
+        }
+
+        events
+    }
+
+    fn queued(&self) -> Vec<u8> {
+        let peer = &self.hosts[1].peers[self.peer as usize];
+        let mut commands: Vec<&ENetOutgoingCommand> = peer
+            .outgoingCommands
+            .iter()
+            .chain(peer.outgoingSendReliableCommands.iter())
+            .collect();
+        commands.sort_by_key(|outgoingCommand| outgoingCommand.queueTime);
+
+        commands
+            .iter()
+            .map(|outgoingCommand| {
+                outgoingCommand.command.command_header().command & ENET_PROTOCOL_COMMAND_MASK as u8
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn each_packet_kind_is_queued_as_its_command_and_delivered_intact() {
+    let small = 100;
+    let large = 3000;
+    let cases = [
+        (
+            ENET_PACKET_FLAG_RELIABLE as u32,
+            small,
+            vec![ENET_PROTOCOL_COMMAND_SEND_RELIABLE],
+        ),
+        (0, small, vec![ENET_PROTOCOL_COMMAND_SEND_UNRELIABLE]),
+        (
+            ENET_PACKET_FLAG_UNSEQUENCED as u32,
+            small,
+            vec![ENET_PROTOCOL_COMMAND_SEND_UNSEQUENCED],
+        ),
+        (
+            ENET_PACKET_FLAG_RELIABLE as u32,
+            large,
+            vec![ENET_PROTOCOL_COMMAND_SEND_FRAGMENT; 3],
+        ),
+        (0, large, vec![ENET_PROTOCOL_COMMAND_SEND_FRAGMENT; 3]),
+        (
+            ENET_PACKET_FLAG_UNRELIABLE_FRAGMENT as u32,
+            large,
+            vec![ENET_PROTOCOL_COMMAND_SEND_UNRELIABLE_FRAGMENT; 3],
+        ),
+    ];
+
+    let mut pair = Pair::new();
+
+    for (flags, length, commands) in cases {
+        let data: Vec<u8> = (0..length).map(|i| (i % 251) as u8).collect();
+        let packet = enet_packet_create(Rc::new(RefCell::new(data.clone())), length, flags);
+        enet_peer_send(&mut pair.hosts[1], pair.peer, 1, packet).unwrap();
+
+        let commands: Vec<u8> = commands.iter().map(|&command| command as u8).collect();
+        assert_eq!(pair.queued(), commands, "flags {flags:#x}, {length} bytes");
+
+        match pair.run().as_slice() {
+            [ENetEvent::RECEIVE {
+                channelID: 1,
+                packet,
+                ..
+            }] => {
+                assert_eq!(packet.data.as_ref().unwrap().borrow()[..], data[..]);
+            }
+            _ => panic!("expected one receive on channel 1 for flags {flags:#x}"),
+        }
+    }
+}
+
+#[test]
+fn send_rejects_channels_the_peer_did_not_negotiate() {
+    let mut pair = Pair::new();
+
+    let packet = enet_packet_create(Rc::new(RefCell::new(vec![0; 4])), 4, 0);
+    assert!(matches!(
+        enet_peer_send(&mut pair.hosts[1], pair.peer, 2, packet),
+        Err(ENetError::ENET_ERROR_INVALID_CHANNEL)
+    ));
+    assert!(pair.queued().is_empty());
+}
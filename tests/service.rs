@@ -0,0 +1,87 @@
+#![allow(non_snake_case)]
+
+//! `enet_host_service`, `enet_host_check_events` and `enet_host_flush` on
+//! hosts joined by an `ENetVirtualNetwork`.
+
+use enet_rust::c_host::*;
+use enet_rust::c_packet::enet_packet_create;
+use enet_rust::c_peer::enet_peer_send;
+use enet_rust::c_virtual::*;
+use enet_rust::h_enet::ENetPacketFlag::*;
+use enet_rust::h_enet::*;
+use enet_rust::h_virtual::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn host(network: &ENetVirtualNetwork, address: &str) -> ENetHost {
+    let transport = enet_virtual_network_bind(network, address.parse().unwrap()).unwrap();
+    let mut host = enet_host_create_with_transport(Box::new(transport), 1, 1, 0, 0).unwrap();
+    enet_host_clock(&mut host, Some(Box::new(network.clock.clone())));
+    host
+}
+
+fn received(event: &ENetEvent) -> Vec<u8> {
+    match event {
+        ENetEvent::RECEIVE { packet, .. } => packet.data.as_ref().unwrap().borrow()[..].to_vec(),
+        _ => panic!("expected a receive event"),
+    }
+}
+
+#[test]
+fn check_events_dispatches_queued_events_without_reading_the_socket() {
+    let network = enet_virtual_network_create(0);
+    let mut server = host(&network, "10.0.0.1:7777");
+    let mut client = host(&network, "10.0.0.2:7777");
+    let peer = enet_host_connect(&mut client, &server.address, 1, 0).unwrap();
+
+    for _ in 0..5 {
+        let mut event = ENetEvent::default();
+        while enet_host_service(&mut server, Some(&mut event), 0).unwrap() > 0 {}
+        while enet_host_service(&mut client, Some(&mut event), 0).unwrap() > 0 {}
+        enet_virtual_network_advance(&network, 10);
+    }
+
+    for i in 0..2u8 {
+        let packet = enet_packet_create(
+            Rc::new(RefCell::new(vec![i])),
+            1,
+            ENET_PACKET_FLAG_RELIABLE as u32,
+        );
+        enet_peer_send(&mut client, peer, 0, packet).unwrap();
+    }
+
+    enet_host_flush(&mut client).unwrap();
+    assert_eq!(network.state.borrow().datagrams.len(), 1);
+
+    let mut event = ENetEvent::default();
+    assert_eq!(
+        enet_host_check_events(&mut server, Some(&mut event)).unwrap(),
+        0
+    );
+    assert_eq!(network.state.borrow().datagrams.len(), 1);
+
+    assert_eq!(
+        enet_host_service(&mut server, Some(&mut event), 0).unwrap(),
+        1
+    );
+    assert_eq!(received(&event), [0]);
+    assert!(network
+        .state
+        .borrow()
+        .datagrams
+        .iter()
+        .all(|datagram| datagram.from == server.address.into()));
+
+    assert_eq!(
+        enet_host_check_events(&mut server, Some(&mut event)).unwrap(),
+        1
+    );
+    assert_eq!(received(&event), [1]);
+
+    assert_eq!(
+        enet_host_check_events(&mut server, Some(&mut event)).unwrap(),
+        0
+    );
+    assert!(matches!(event, ENetEvent::NONE { .. }));
+    assert_eq!(enet_host_check_events(&mut server, None).unwrap(), 0);
+}
@@ -0,0 +1,144 @@
+#![allow(non_snake_case)]
+
+//! `enet_socket_set_option` and `enet_socket_get_option` reach the kernel
+//! for every `ENetSocketOption`, also through the transport of a host.
+
+use enet_rust::c_host::*;
+use enet_rust::c_virtual::*;
+use enet_rust::c_win32::*;
+use enet_rust::h_enet::ENetSocketOption::*;
+use enet_rust::h_enet::*;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, UdpSocket};
+
+fn create_socket() -> UdpSocket {
+    let address = ENetAddress::from(SocketAddr::from(([127, 0, 0, 1], 0)));
+
+    enet_socket_create(&address, ENetHostOption::ENET_HOSTOPT_IPV4).unwrap()
+}
+
+fn get_option(socket: &UdpSocket, option: ENetSocketOption) -> i32 {
+    let mut value = -1;
+    enet_socket_get_option(socket, option, &mut value).unwrap();
+    value
+}
+
+#[test]
+fn flags_round_trip() {
+    let socket = create_socket();
+
+    for option in [
+        ENET_SOCKOPT_NONBLOCK,
+        ENET_SOCKOPT_BROADCAST,
+        ENET_SOCKOPT_REUSEADDR,
+    ] {
+        for value in [1, 0] {
+            enet_socket_set_option(&socket, option, value).unwrap();
+            assert_eq!(get_option(&socket, option), value);
+        }
+    }
+}
+
+#[test]
+fn buffer_sizes_reach_the_kernel() {
+    let socket = create_socket();
+
+    for option in [ENET_SOCKOPT_RCVBUF, ENET_SOCKOPT_SNDBUF] {
+        let before = get_option(&socket, option);
+
+        enet_socket_set_option(&socket, option, 8192).unwrap();
+        let after = get_option(&socket, option);
+
+        // Linux doubles the requested size to account for bookkeeping.
+        assert!((8192..=16384).contains(&after), "{after}");
+        assert_ne!(before, after);
+    }
+}
+
+#[test]
+fn timeouts_are_in_milliseconds() {
+    let socket = create_socket();
+
+    for option in [ENET_SOCKOPT_RCVTIMEO, ENET_SOCKOPT_SNDTIMEO] {
+        enet_socket_set_option(&socket, option, 1000).unwrap();
+        assert_eq!(get_option(&socket, option), 1000);
+
+        enet_socket_set_option(&socket, option, 0).unwrap();
+        assert_eq!(get_option(&socket, option), 0);
+    }
+}
+
+#[test]
+fn ttl_round_trips() {
+    let socket = create_socket();
+
+    enet_socket_set_option(&socket, ENET_SOCKOPT_TTL, 17).unwrap();
+    assert_eq!(get_option(&socket, ENET_SOCKOPT_TTL), 17);
+}
+
+#[test]
+fn error_is_read_only_and_clear_on_a_fresh_socket() {
+    let socket = create_socket();
+
+    assert_eq!(get_option(&socket, ENET_SOCKOPT_ERROR), 0);
+    assert!(enet_socket_set_option(&socket, ENET_SOCKOPT_ERROR, 0).is_err());
+}
+
+#[test]
+fn nodelay_is_rejected_on_a_datagram_socket() {
+    let socket = create_socket();
+
+    assert!(matches!(
+        enet_socket_set_option(&socket, ENET_SOCKOPT_NODELAY, 1),
+        Err(ENetError::ENET_ERROR_SOCKET(_))
+    ));
+}
+
+#[test]
+fn waiting_leaves_blocking_mode_and_timeout_alone() {
+    let socket = create_socket();
+    enet_socket_set_option(&socket, ENET_SOCKOPT_NONBLOCK, 0).unwrap();
+    enet_socket_set_option(&socket, ENET_SOCKOPT_RCVTIMEO, 1000).unwrap();
+
+    let mut condition = ENetSocketWait::ENET_SOCKET_WAIT_RECEIVE as u32;
+    enet_socket_wait(&socket, &mut condition, 10).unwrap();
+    assert_eq!(condition, ENetSocketWait::ENET_SOCKET_WAIT_NONE as u32);
+
+    assert_eq!(get_option(&socket, ENET_SOCKOPT_NONBLOCK), 0);
+    assert_eq!(get_option(&socket, ENET_SOCKOPT_RCVTIMEO), 1000);
+}
+
+#[test]
+fn options_reach_the_socket_of_a_running_host() {
+    let address = ENetAddress::from(SocketAddr::from(([127, 0, 0, 1], 0)));
+    let host = enet_host_create(&address, 1, 1, 0, 0, ENetHostOption::ENET_HOSTOPT_IPV4).unwrap();
+
+    let mut value = -1;
+    enet_socket_get_option(&*host.socket, ENET_SOCKOPT_NONBLOCK, &mut value).unwrap();
+    assert_eq!(value, 1);
+
+    enet_socket_set_option(&*host.socket, ENET_SOCKOPT_TTL, 17).unwrap();
+    enet_socket_get_option(&*host.socket, ENET_SOCKOPT_TTL, &mut value).unwrap();
+    assert_eq!(value, 17);
+}
+
+#[test]
+fn transports_without_socket_options_report_unsupported() {
+    let network = enet_virtual_network_create(0);
+    let transport = enet_virtual_network_bind(&network, "10.0.0.1:7777".parse().unwrap()).unwrap();
+    let host = enet_host_create_with_transport(Box::new(transport), 1, 1, 0, 0).unwrap();
+
+    let mut value = -1;
+    for result in [
+        enet_socket_set_option(&*host.socket, ENET_SOCKOPT_TTL, 17),
+        enet_socket_get_option(&*host.socket, ENET_SOCKOPT_TTL, &mut value),
+    ] {
+        match result {
+            Err(ENetError::ENET_ERROR_SOCKET(error)) => {
+                assert_eq!(error.kind(), ErrorKind::Unsupported)
+            }
+            _ => panic!("expected an unsupported socket error"),
+        }
+    }
+    assert_eq!(value, -1);
+}
@@ -0,0 +1,183 @@
+#![allow(non_snake_case)]
+
+//! The adaptive packet throttle: its arithmetic with parameters taken from
+//! the wire, its response to a congested link and THROTTLE_CONFIGURE.
+
+use enet_rust::c_host::*;
+use enet_rust::c_packet::enet_packet_create;
+use enet_rust::c_peer::*;
+use enet_rust::c_protocol::*;
+use enet_rust::c_sansio::*;
+use enet_rust::c_virtual::*;
+use enet_rust::h_enet::ENetPacketFlag::*;
+use enet_rust::h_enet::*;
+use enet_rust::h_protocol::*;
+use enet_rust::h_virtual::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn host(network: &ENetVirtualNetwork, address: &str) -> ENetHost {
+    let transport = enet_virtual_network_bind(network, address.parse().unwrap()).unwrap();
+    let mut host = enet_host_create_with_transport(Box::new(transport), 1, 1, 0, 0).unwrap();
+    enet_host_clock(&mut host, Some(Box::new(network.clock.clone())));
+    host
+}
+
+fn service(host: &mut ENetHost) {
+    let mut event = ENetEvent::default();
+    while enet_host_service(host, Some(&mut event), 0).unwrap() > 0 {}
+}
+
+fn send(host: &mut ENetHost, peer: u16, flags: u32) {
+    let packet = enet_packet_create(Rc::new(RefCell::new(vec![0; 4])), 4, flags);
+    enet_peer_send(host, peer, 0, packet).unwrap();
+}
+
+#[test]
+fn acceleration_from_the_wire_saturates_at_the_limit() {
+    let sansIO = enet_sans_io_create("10.0.0.1:7777".parse().unwrap(), 0);
+    let mut host = enet_host_create_sans_io(&sansIO, 1, 1, 0, 0).unwrap();
+    let peer = &mut host.peers[0];
+
+    peer.lastRoundTripTime = 100;
+    peer.lastRoundTripTimeVariance = 10;
+    peer.packetThrottle = 16;
+    peer.packetThrottleAcceleration = u32::MAX;
+
+    assert_eq!(enet_peer_throttle(peer, 50), 1);
+    assert_eq!(peer.packetThrottle, peer.packetThrottleLimit);
+}
+
+#[test]
+fn congestion_lowers_the_throttle_and_discards_unreliable_packets() {
+    let network = enet_virtual_network_create(3);
+    enet_virtual_network_set_conditions(
+        &network,
+        ENetVirtualConditions {
+            latency: 20,
+            ..ENetVirtualConditions::default()
+        },
+    );
+
+    let mut server = host(&network, "10.0.0.1:7777");
+    let mut client = host(&network, "10.0.0.2:7777");
+    let peer = enet_host_connect(&mut client, &server.address, 1, 0).unwrap();
+
+    for _ in 0..(ENET_PEER_PACKET_THROTTLE_INTERVAL + 1000) / 10 {
+        service(&mut server);
+        service(&mut client);
+        enet_virtual_network_advance(&network, 10);
+    }
+
+    assert_eq!(
+        client.peers[peer as usize].packetThrottle,
+        ENET_PEER_PACKET_THROTTLE_SCALE
+    );
+
+    enet_virtual_network_set_conditions(
+        &network,
+        ENetVirtualConditions {
+            latency: 60,
+            loss: 0.3,
+            ..ENetVirtualConditions::default()
+        },
+    );
+
+    for _ in 0..100 {
+        send(&mut client, peer, ENET_PACKET_FLAG_RELIABLE as u32);
+        service(&mut server);
+        service(&mut client);
+        enet_virtual_network_advance(&network, 10);
+    }
+
+    enet_virtual_network_set_conditions(
+        &network,
+        ENetVirtualConditions {
+            latency: 60,
+            ..ENetVirtualConditions::default()
+        },
+    );
+
+    while !client.peers[peer as usize].sentReliableCommands.is_empty() {
+        service(&mut server);
+        service(&mut client);
+        enet_virtual_network_advance(&network, 10);
+    }
+
+    let packetThrottle = client.peers[peer as usize].packetThrottle;
+    assert!(packetThrottle < ENET_PEER_PACKET_THROTTLE_SCALE);
+
+    for _ in 0..ENET_PEER_PACKET_THROTTLE_SCALE {
+        send(&mut client, peer, 0);
+    }
+
+    let packetsSent = client.peers[peer as usize].packetsSent;
+    enet_host_flush(&mut client).unwrap();
+
+    let peer = &client.peers[peer as usize];
+    assert!(peer.outgoingCommands.is_empty());
+    assert_eq!(peer.packetsSent - packetsSent, packetThrottle + 1);
+}
+
+#[test]
+fn throttle_configure_is_sent_and_applied_by_the_receiver() {
+    let sansIOs = [
+        enet_sans_io_create("10.0.0.1:7777".parse().unwrap(), 0),
+        enet_sans_io_create("10.0.0.2:7777".parse().unwrap(), 0),
+    ];
+    let mut hosts: Vec<ENetHost> = sansIOs
+        .iter()
+        .map(|sansIO| enet_host_create_sans_io(sansIO, 1, 1, 0, 0).unwrap())
+        .collect();
+
+    let serverAddress = hosts[0].address;
+    let peer = enet_host_connect(&mut hosts[1], &serverAddress, 1, 0).unwrap();
+
+    let mut time = 0;
+    for _ in 0..8 {
+        for i in 0..2 {
+            let mut event = ENetEvent::default();
+            while enet_sans_io_service(&sansIOs[i], &mut hosts[i], time, Some(&mut event)).unwrap()
+                > 0
+            {}
+
+            while let Some(datagram) = enet_sans_io_poll_datagram(&sansIOs[i]) {
+                enet_sans_io_receive(
+                    &sansIOs[1 - i],
+                    time + 10,
+                    sansIOs[i].address,
+                    &datagram.data,
+                );
+            }
+        }
+
+        time += 10;
+    }
+
+    assert_eq!(
+        hosts[0].peers[0].packetThrottleInterval,
+        ENET_PEER_PACKET_THROTTLE_INTERVAL
+    );
+
+    enet_peer_throttle_configure(&mut hosts[1], peer, 1000, 5, 7);
+    enet_sans_io_service(&sansIOs[1], &mut hosts[1], time, None).unwrap();
+
+    let datagram = enet_sans_io_poll_datagram(&sansIOs[1]).unwrap();
+    let (_, offset) = enet_protocol_decode_header(&datagram.data).unwrap();
+    match enet_protocol_decode_command(&datagram.data[offset..]).unwrap() {
+        ENetProtocol::throttleConfigure(command) => {
+            assert_eq!(command.packetThrottleInterval, 1000);
+            assert_eq!(command.packetThrottleAcceleration, 5);
+            assert_eq!(command.packetThrottleDeceleration, 7);
+        }
+        _ => panic!("expected THROTTLE_CONFIGURE"),
+    }
+
+    enet_sans_io_receive(&sansIOs[0], time, sansIOs[1].address, &datagram.data);
+    enet_sans_io_service(&sansIOs[0], &mut hosts[0], time, None).unwrap();
+
+    let peer = &hosts[0].peers[0];
+    assert_eq!(peer.packetThrottleInterval, 1000);
+    assert_eq!(peer.packetThrottleAcceleration, 5);
+    assert_eq!(peer.packetThrottleDeceleration, 7);
+}
@@ -0,0 +1,27 @@
+#![allow(non_snake_case)]
+
+//! `enet_time_get` follows the monotonic clock and `enet_time_set` rebases
+//! it. The time base is process-wide, so this binary holds a single test.
+
+use enet_rust::c_win32::*;
+use enet_rust::h_time::*;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn time_advances_and_can_be_rebased() {
+    let before = enet_time_get();
+    thread::sleep(Duration::from_millis(20));
+    let elapsed = ENET_TIME_DIFFERENCE(enet_time_get(), before);
+    assert!((20..1000).contains(&elapsed), "{elapsed}");
+
+    enet_time_set(1_000_000);
+    let elapsed = ENET_TIME_DIFFERENCE(enet_time_get(), 1_000_000);
+    assert!(elapsed < 100, "{elapsed}");
+
+    enet_time_set(u32::MAX - 5);
+    thread::sleep(Duration::from_millis(20));
+    let now = enet_time_get();
+    assert!(ENET_TIME_GREATER(now, u32::MAX - 5));
+    assert!(now < 1000, "{now}");
+}
@@ -0,0 +1,127 @@
+#![allow(non_snake_case)]
+
+//! Two hosts connected through an in-memory `ENetTransport` instead of UDP
+//! sockets.
+
+use enet_rust::c_host::*;
+use enet_rust::c_packet::enet_packet_create;
+use enet_rust::c_peer::enet_peer_send;
+use enet_rust::h_enet::ENetSocketWait::*;
+use enet_rust::h_enet::*;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::net::SocketAddr;
+use std::rc::Rc;
+
+type Mailboxes = Rc<RefCell<HashMap<SocketAddr, VecDeque<(SocketAddr, Vec<u8>)>>>>;
+
+struct MemoryTransport {
+    address: SocketAddr,
+    mailboxes: Mailboxes,
+}
+
+impl MemoryTransport {
+    fn new(mailboxes: &Mailboxes, address: &str) -> MemoryTransport {
+        let address = address.parse().unwrap();
+        mailboxes.borrow_mut().insert(address, VecDeque::new());
+
+        MemoryTransport {
+            address,
+            mailboxes: Rc::clone(mailboxes),
+        }
+    }
+}
+
+impl ENetTransport for MemoryTransport {
+    fn send_to(&self, data: &[u8], address: SocketAddr) -> io::Result<usize> {
+        if let Some(mailbox) = self.mailboxes.borrow_mut().get_mut(&address) {
+            mailbox.push_back((self.address, data.to_vec()));
+        }
+
+        Ok(data.len())
+    }
+
+    fn recv_from(&self, data: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        let mut mailboxes = self.mailboxes.borrow_mut();
+        let (address, datagram) = mailboxes
+            .get_mut(&self.address)
+            .unwrap()
+            .pop_front()
+            .ok_or(io::ErrorKind::WouldBlock)?;
+
+        data[..datagram.len()].copy_from_slice(&datagram);
+
+        Ok((datagram.len(), address))
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        Ok(self.address)
+    }
+
+    fn wait(&self, condition: &mut u32, _timeout: u32) -> io::Result<()> {
+        let receive = *condition & ENET_SOCKET_WAIT_RECEIVE as u32 != 0;
+        *condition = ENET_SOCKET_WAIT_NONE as u32;
+
+        if receive && !self.mailboxes.borrow()[&self.address].is_empty() {
+            *condition |= ENET_SOCKET_WAIT_RECEIVE as u32;
+        }
+
+        Ok(())
+    }
+}
+
+fn service(host: &mut ENetHost) -> Vec<ENetEvent> {
+    let mut events = Vec::new();
+
+    loop {
+        let mut event = ENetEvent::default();
+        if enet_host_service(host, Some(&mut event), 0).unwrap() == 0 {
+            return events;
+        }
+
+        events.push(event);
+    }
+}
+
+#[test]
+fn hosts_exchange_packets_over_a_custom_transport() {
+    let mailboxes = Mailboxes::default();
+    let clock = ENetManualClock::new(0);
+
+    let mut hosts: Vec<ENetHost> = ["10.0.0.1:7777", "10.0.0.2:7777"]
+        .iter()
+        .map(|address| {
+            let transport = MemoryTransport::new(&mailboxes, address);
+            let mut host =
+                enet_host_create_with_transport(Box::new(transport), 1, 1, 0, 0).unwrap();
+            enet_host_clock(&mut host, Some(Box::new(clock.clone())));
+            host
+        })
+        .collect();
+
+    let serverAddress = hosts[0].address;
+    let peer = enet_host_connect(&mut hosts[1], &serverAddress, 1, 42).unwrap();
+
+    let mut connected = false;
+    for _ in 0..4 {
+        for host in hosts.iter_mut() {
+            connected |= service(host)
+                .iter()
+                .any(|event| matches!(event, ENetEvent::CONNECT { data: 42, .. }));
+        }
+    }
+    assert!(connected);
+
+    let packet = enet_packet_create(Rc::new(RefCell::new(b"hello".to_vec())), 5, 1);
+    enet_peer_send(&mut hosts[1], peer, 0, packet).unwrap();
+    service(&mut hosts[1]);
+
+    let events = service(&mut hosts[0]);
+    match events.as_slice() {
+        [ENetEvent::RECEIVE { packet, .. }] => {
+            assert_eq!(&packet.data.as_ref().unwrap().borrow()[..], b"hello");
+        }
+        _ => panic!("expected one receive event"),
+    }
+}
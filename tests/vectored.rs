@@ -0,0 +1,202 @@
+#![allow(non_snake_case)]
+
+//! Multi-buffer sends and receives on a `UdpSocket` gather and scatter in
+//! `sendmsg` and `recvmsg`, with no merge buffer and no allocation, and
+//! buffer lists that do not fit are rejected rather than indexed.
+
+use enet_rust::c_win32::*;
+use enet_rust::h_enet::*;
+use enet_rust::h_win32::ENetBuffer;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, UdpSocket};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn create_socket() -> UdpSocket {
+    let address = ENetAddress::from(SocketAddr::from(([127, 0, 0, 1], 0)));
+    let socket = enet_socket_create(&address, ENetHostOption::ENET_HOSTOPT_IPV4).unwrap();
+    enet_socket_set_option(&socket, ENetSocketOption::ENET_SOCKOPT_RCVTIMEO, 1000).unwrap();
+    socket
+}
+
+fn local_address(socket: &UdpSocket) -> ENetAddress {
+    let mut address = ENetAddress::new();
+    enet_socket_get_address(socket, &mut address).unwrap();
+    address
+}
+
+#[test]
+fn buffers_are_gathered_and_scattered_without_allocating() {
+    let sender = create_socket();
+    let receiver = create_socket();
+    let receiverAddress = local_address(&receiver);
+
+    let data: [&[u8]; 3] = [b"head", b"ignored", b"payload"];
+    let buffers = [
+        ENetBuffer {
+            dataID: 0,
+            dataLength: 4,
+        },
+        ENetBuffer {
+            dataID: 2,
+            dataLength: 7,
+        },
+        ENetBuffer {
+            dataID: 0,
+            dataLength: 2,
+        },
+    ];
+
+    // Warm up anything lazily allocated on the first send.
+    enet_socket_send(&sender, &receiverAddress, &data, &buffers, 3).unwrap();
+
+    let before = ALLOCATIONS.with(Cell::get);
+    let sent = enet_socket_send(&sender, &receiverAddress, &data, &buffers, 3).unwrap();
+    assert_eq!(ALLOCATIONS.with(Cell::get), before);
+    assert_eq!(sent, 13);
+
+    for _ in 0..2 {
+        let before = ALLOCATIONS.with(Cell::get);
+        let mut first = [0u8; 5];
+        let mut second = [0u8; 16];
+        let mut data: [&mut [u8]; 2] = [&mut first, &mut second];
+        let mut buffers = [
+            ENetBuffer {
+                dataID: 0,
+                dataLength: 5,
+            },
+            ENetBuffer {
+                dataID: 1,
+                dataLength: 16,
+            },
+        ];
+
+        let mut address = ENetAddress::new();
+        let received =
+            enet_socket_receive(&receiver, &mut address, &mut data, &mut buffers, 2).unwrap();
+        assert_eq!(ALLOCATIONS.with(Cell::get), before);
+
+        assert_eq!(received, 13);
+        assert!(address == local_address(&sender));
+        assert_eq!(&first, b"headp");
+        assert_eq!(&second[..8], b"ayloadhe");
+    }
+}
+
+fn is_invalid_input(result: Result<usize, ENetError>) -> bool {
+    matches!(result, Err(ENetError::ENET_ERROR_SOCKET(e)) if e.kind() == ErrorKind::InvalidInput)
+}
+
+#[test]
+fn buffer_lists_that_do_not_fit_are_rejected() {
+    let socket = create_socket();
+    let address = local_address(&socket);
+
+    let data: [&[u8]; 1] = [b"data"];
+    let outOfRange = [ENetBuffer {
+        dataID: ENET_BUFFER_MAXIMUM as usize,
+        dataLength: 4,
+    }];
+    let tooLong = [ENetBuffer {
+        dataID: 0,
+        dataLength: 5,
+    }];
+    let tooMany = [ENetBuffer {
+        dataID: 0,
+        dataLength: 1,
+    }; ENET_BUFFER_MAXIMUM as usize + 1];
+
+    assert!(is_invalid_input(enet_socket_send(
+        &socket,
+        &address,
+        &data,
+        &outOfRange,
+        1
+    )));
+    assert!(is_invalid_input(enet_socket_send(
+        &socket, &address, &data, &tooLong, 1
+    )));
+    assert!(is_invalid_input(enet_socket_send(
+        &socket,
+        &address,
+        &data,
+        &tooMany,
+        tooMany.len()
+    )));
+
+    let mut buffer = [0u8; 4];
+    let mut data: [&mut [u8]; 1] = [&mut buffer];
+    let mut outOfRange = outOfRange;
+    let mut received = ENetAddress::new();
+    assert!(is_invalid_input(enet_socket_receive(
+        &socket,
+        &mut received,
+        &mut data,
+        &mut outOfRange,
+        1
+    )));
+
+    let mut buffer = [0u8; 4];
+    let mut data: [&mut [u8]; 1] = [&mut buffer];
+    let mut tooMany = tooMany;
+    assert!(is_invalid_input(enet_socket_receive(
+        &socket,
+        &mut received,
+        &mut data,
+        &mut tooMany,
+        ENET_BUFFER_MAXIMUM as usize + 1
+    )));
+}
+
+#[test]
+fn datagrams_longer_than_the_mtu_are_scattered_whole() {
+    let sender = create_socket();
+    let receiver = create_socket();
+
+    let payload: Vec<u8> = (0..6000).map(|i| i as u8).collect();
+    sender
+        .send_to(&payload, receiver.local_addr().unwrap())
+        .unwrap();
+
+    let mut first = vec![0u8; 4000];
+    let mut second = vec![0u8; 4000];
+    let mut data: [&mut [u8]; 2] = [&mut first, &mut second];
+    let mut buffers = [
+        ENetBuffer {
+            dataID: 0,
+            dataLength: 4000,
+        },
+        ENetBuffer {
+            dataID: 1,
+            dataLength: 4000,
+        },
+    ];
+
+    let mut address = ENetAddress::new();
+    let received =
+        enet_socket_receive(&receiver, &mut address, &mut data, &mut buffers, 2).unwrap();
+
+    assert_eq!(received, 6000);
+    assert_eq!(&first[..], &payload[..4000]);
+    assert_eq!(&second[..2000], &payload[4000..]);
+}
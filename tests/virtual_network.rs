@@ -0,0 +1,195 @@
+#![allow(non_snake_case)]
+
+//! Hosts connected through an `ENetVirtualNetwork` with simulated latency,
+//! loss, duplication and reordering.
+
+use enet_rust::c_host::*;
+use enet_rust::c_packet::enet_packet_create;
+use enet_rust::c_peer::enet_peer_send;
+use enet_rust::c_virtual::*;
+use enet_rust::h_enet::ENetPacketFlag::*;
+use enet_rust::h_enet::*;
+use enet_rust::h_virtual::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+struct Pair {
+    network: ENetVirtualNetwork,
+    server: ENetHost,
+    client: ENetHost,
+    peer: u16,
+    log: Vec<String>,
+    received: Vec<Vec<u8>>,
+}
+
+fn host(network: &ENetVirtualNetwork, address: &str, randomSeed: u32) -> ENetHost {
+    let transport = enet_virtual_network_bind(network, address.parse().unwrap()).unwrap();
+    let mut host = enet_host_create_with_transport(Box::new(transport), 1, 1, 0, 0).unwrap();
+    enet_host_clock(&mut host, Some(Box::new(network.clock.clone())));
+    host.randomSeed = randomSeed;
+    host
+}
+
+impl Pair {
+    fn new(seed: u64, conditions: ENetVirtualConditions) -> Pair {
+        let network = enet_virtual_network_create(seed);
+        enet_virtual_network_set_conditions(&network, conditions);
+
+        let server = host(&network, "10.0.0.1:7777", 1);
+        let mut client = host(&network, "10.0.0.2:7777", 2);
+        let peer = enet_host_connect(&mut client, &server.address, 1, 0).unwrap();
+
+        Pair {
+            network,
+            server,
+            client,
+            peer,
+            log: Vec::new(),
+            received: Vec::new(),
+        }
+    }
+
+    fn service(&mut self) {
+        let now = self.network.clock.now();
+
+        for (name, host) in [("server", &mut self.server), ("client", &mut self.client)] {
+            loop {
+                let mut event = ENetEvent::default();
+                if enet_host_service(host, Some(&mut event), 0).unwrap() == 0 {
+                    break;
+                }
+
+                match event {
+                    ENetEvent::CONNECT { .. } => self.log.push(format!("{now} {name} connect")),
+
+                    ENetEvent::DISCONNECT { .. } => {
+                        self.log.push(format!("{now} {name} disconnect"))
+                    }
+
+                    ENetEvent::RECEIVE { packet, .. } => {
+                        let data = packet.data.as_ref().unwrap().borrow()[..].to_vec();
+                        self.log.push(format!("{now} {name} receive {data:?}"));
+                        self.received.push(data);
+                    }
+
+                    ENetEvent::NONE { .. } => {}
+                }
+            }
+        }
+    }
+
+    fn run(&mut self, milliseconds: u32) {
+        for _ in 0..milliseconds / 10 {
+            self.service();
+            enet_virtual_network_advance(&self.network, 10);
+        }
+        self.service();
+    }
+
+    fn connected(&self) -> bool {
+        self.client.peers[self.peer as usize].state == ENetPeerState::ENET_PEER_STATE_CONNECTED
+    }
+
+    fn send(&mut self, data: &[u8], flags: u32) {
+        let packet = enet_packet_create(Rc::new(RefCell::new(data.to_vec())), data.len(), flags);
+        enet_peer_send(&mut self.client, self.peer, 0, packet).unwrap();
+    }
+}
+
+fn reliable_transfer(seed: u64, conditions: ENetVirtualConditions) -> Pair {
+    let mut pair = Pair::new(seed, conditions);
+
+    while !pair.connected() {
+        pair.run(10);
+    }
+
+    for i in 0..50u8 {
+        pair.send(&[i; 8], ENET_PACKET_FLAG_RELIABLE as u32);
+        pair.run(20);
+    }
+
+    for _ in 0..1000 {
+        if pair.received.len() >= 50 {
+            break;
+        }
+        pair.run(100);
+    }
+
+    pair
+}
+
+fn expected() -> Vec<Vec<u8>> {
+    (0..50u8).map(|i| vec![i; 8]).collect()
+}
+
+#[test]
+fn lossy_network_is_deterministic_for_a_seed() {
+    let conditions = ENetVirtualConditions {
+        latency: 20,
+        jitter: 10,
+        loss: 0.3,
+        ..ENetVirtualConditions::default()
+    };
+
+    let first = reliable_transfer(7, conditions);
+    let second = reliable_transfer(7, conditions);
+
+    assert_eq!(first.received, expected());
+    assert_eq!(first.log, second.log);
+}
+
+#[test]
+fn duplicated_and_reordered_datagrams_deliver_each_packet_once() {
+    let conditions = ENetVirtualConditions {
+        latency: 20,
+        jitter: 30,
+        duplicate: 1.0,
+        reorder: 0.5,
+        ..ENetVirtualConditions::default()
+    };
+
+    let pair = reliable_transfer(11, conditions);
+
+    assert_eq!(pair.received, expected());
+}
+
+#[test]
+fn datagrams_above_the_mtu_are_dropped() {
+    let conditions = ENetVirtualConditions {
+        latency: 5,
+        mtu: 200,
+        ..ENetVirtualConditions::default()
+    };
+
+    let mut pair = Pair::new(3, conditions);
+    pair.run(100);
+    assert!(pair.connected());
+
+    pair.send(&[1; 1000], ENET_PACKET_FLAG_RELIABLE as u32);
+    pair.run(2000);
+    assert!(pair.received.is_empty());
+
+    let mut pair = Pair::new(3, conditions);
+    pair.run(100);
+
+    pair.send(&[1; 100], ENET_PACKET_FLAG_RELIABLE as u32);
+    pair.run(100);
+    assert_eq!(pair.received, vec![vec![1; 100]]);
+}
+
+#[test]
+fn unbound_addresses_drop_datagrams_and_rebinding_is_refused() {
+    let network = enet_virtual_network_create(0);
+    let address = "10.0.0.1:7777".parse().unwrap();
+
+    let transport = enet_virtual_network_bind(&network, address).unwrap();
+    assert!(enet_virtual_network_bind(&network, address).is_err());
+
+    drop(transport);
+    let transport = enet_virtual_network_bind(&network, address).unwrap();
+
+    transport
+        .send_to(b"lost", "10.0.0.2:7777".parse().unwrap())
+        .unwrap();
+    assert!(network.state.borrow().datagrams.is_empty());
+}